@@ -0,0 +1,1738 @@
+//! The expansion behind `#[derive(Builder)]` and `#[builder]`, working on `proc_macro2` tokens
+//! so that it can be called from build scripts, tests and other macros. The derive_builder_impl
+//! crate wraps it into the actual procedural macros.
+
+use indoc::indoc;
+use proc_macro2::{Delimiter, Spacing, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote};
+
+use syn::visit_mut::{self, VisitMut};
+use syn::Attribute;
+use syn::Expr;
+use syn::Field;
+use syn::LitStr;
+use syn::Type;
+use syn::{DeriveInput, Ident};
+use syn::{FnArg, ImplItem, Item, ItemFn, ItemImpl, Pat, Signature, Visibility};
+use syn::{GenericParam, Lifetime, LifetimeParam, TypeImplTrait, TypeReference};
+
+struct AnnotatedField {
+    /// The field name
+    name: Ident,
+    /// The field type, like `u8` or `Option<String>`
+    ty: Type,
+    /// Is this field an `Option` field?
+    is_optional: bool,
+    /// Optional name of a one-by-one setter function, declared via:
+    /// ```rust
+    /// # use derive_builder::Builder;
+    /// # #[derive(Builder)]
+    /// # struct Foo {
+    ///     #[builder(each = "my_field_setter")]
+    ///     my_field: Vec<String>,
+    /// # }
+    /// ```
+    /// In this case, a setter function named `my_field_setter` will be created that adds to the
+    /// growing `Vec<String>`, taking a `String`, and can be called repeatedly.
+    one_by_one_setter: Option<Ident>,
+    /// If the field is an `Option` field, this type will represent what `Type` is in
+    /// the `Option`. If the field is a `Vec`, it will represent what is in the `Vec`.
+    inner_type: Option<Type>,
+    /// Optional type used to hold the field while building, and the expression converting
+    /// it into the field type, declared via:
+    /// ```rust
+    /// # use derive_builder::Builder;
+    /// # use std::sync::Arc;
+    /// # #[derive(Builder)]
+    /// # struct Foo {
+    ///     #[builder(field(ty = "String", build = "Arc::from(name)"))]
+    ///     name: Arc<str>,
+    /// # }
+    /// ```
+    /// The expression sees the built-up value under the field's own name.
+    storage: Option<(Type, Expr)>,
+    /// Optional value used when the setter was never called, declared via
+    /// `#[builder(default)]` for `Default::default()` or `#[builder(default = "...")]`
+    default: Option<Expr>,
+    /// The `default` of a `#[builder(default)]` given without a value, which `const` builders
+    /// cannot use since `Default::default()` is not a `const fn`
+    bare_default: Option<syn::Path>,
+    /// Does the main setter of an `Option` field take the inner type? This is the default,
+    /// and is turned off via `#[builder(setter(strip_option = false))]`
+    strip_option: bool,
+    /// The doc comment on the field, one line per `///` line
+    doc: String,
+    /// Is the field left out of `Foo::diff()`? Declared via `#[builder(skip_diff)]` for fields
+    /// that are not `PartialEq` or `Clone`
+    skip_diff: bool,
+    /// Is the field left out of `set_by_name()` and `from_env()`? Declared via
+    /// `#[builder(skip_set_by_name)]` for fields that are not `FromStr`
+    skip_set_by_name: bool,
+    parsed: Option<TokenStream>,
+}
+
+/// Everything that can be declared on the struct itself via `#[builder(...)]`.
+#[derive(Default)]
+struct StructAttrs {
+    /// Generate an owned, `const fn` builder, declared via `#[builder(const)]`.
+    is_const: bool,
+    /// Generate a *Foo*Patch alongside the builder, declared via `#[builder(patch)]`.
+    patch: bool,
+    /// Generate `set_by_name()` and `from_env()` on the builder, declared via
+    /// `#[builder(set_by_name)]`.
+    set_by_name: bool,
+    /// Path to the derive_builder crate used by the generated code, declared via
+    /// `#[builder(crate = "...")]` when derive_builder is re-exported by another crate.
+    crate_path: Option<syn::Path>,
+}
+
+impl StructAttrs {
+    /// The path generated code uses to refer to the derive_builder crate.
+    fn crate_path(&self) -> syn::Path {
+        self.crate_path
+            .clone()
+            .unwrap_or_else(|| syn::parse_quote!(::derive_builder))
+    }
+}
+
+/// Everything that can be declared on a field via `#[builder(...)]`.
+#[derive(Default)]
+struct FieldAttrs {
+    each: Option<Ident>,
+    storage: Option<(Type, Expr)>,
+    default: Option<Expr>,
+    bare_default: Option<syn::Path>,
+    strip_option: Option<syn::LitBool>,
+    skip_diff: bool,
+    skip_set_by_name: bool,
+}
+
+impl From<&Field> for AnnotatedField {
+    fn from(field: &Field) -> Self {
+        let ident = &field.ident;
+        let name = ident.clone().expect("Field has a name");
+        Self::new(name, field.ty.clone(), &field.attrs)
+    }
+}
+
+impl AnnotatedField {
+    /// Classifies a named, typed value carrying `#[builder(...)]` attributes. This is shared by
+    /// struct fields and by the arguments of functions marked `#[builder]`.
+    fn new(name: Ident, ty: Type, attrs: &[Attribute]) -> Self {
+        let doc = get_doc(attrs);
+        let (attrs, mut parsed) = match get_builder_attrs(attrs) {
+            Ok(attrs) => (attrs, None),
+            Err(ts) => (FieldAttrs::default(), Some(ts)),
+        };
+        // fields with custom storage are classified by what they are built from
+        let storage_ty = attrs.storage.as_ref().map_or(&ty, |(t, _)| t);
+        let opt_typ = get_option_type(storage_ty);
+        let is_optional = opt_typ.is_some();
+        let strip_option = attrs.strip_option.as_ref().is_none_or(|b| b.value);
+        if let Some(lit) = attrs.strip_option.filter(|_| !is_optional) {
+            let ts = syn::Error::new_spanned(lit, "`strip_option` only applies to `Option` fields")
+                .into_compile_error();
+            parsed = Some(ts);
+        }
+        let setter = attrs.each;
+        let inner_type = if let Some(t) = opt_typ {
+            Some(t.clone())
+        } else if setter.is_some() {
+            let t = get_vec_type(storage_ty).unwrap().clone();
+            Some(t)
+        } else {
+            None
+        };
+
+        Self {
+            name,
+            ty,
+            is_optional,
+            one_by_one_setter: setter,
+            inner_type,
+            storage: attrs.storage,
+            default: attrs.default,
+            bare_default: attrs.bare_default,
+            strip_option,
+            doc,
+            skip_diff: attrs.skip_diff,
+            skip_set_by_name: attrs.skip_set_by_name,
+            parsed,
+        }
+    }
+
+    /// Does `build()` need the setter to have been called? This is the case unless the field is
+    /// an `Option`, has a `#[builder(default)]` or is filled one element at a time.
+    fn is_required(&self) -> bool {
+        !self.is_optional && self.default.is_none() && self.one_by_one_setter.is_none()
+    }
+
+    /// The type held by the *Foo*Builder for this field, which is the field type unless
+    /// `#[builder(field(ty = "..."))]` was given.
+    fn storage_type(&self) -> &Type {
+        self.storage.as_ref().map_or(&self.ty, |(t, _)| t)
+    }
+
+    /// This function creates individual lines used to define the *Foo*Builder struct.
+    /// For example, if we have
+    /// ```rust
+    /// struct Foo {
+    ///     alpha: String,
+    ///     beta: Option<u8>,
+    ///     gamma: Vec<String>,
+    /// }
+    /// ```
+    /// then this function will generate one of the definition lines for *Foo*Builder, like
+    /// ```rust
+    /// # struct Foo {
+    ///     beta: Option<Option<String>>,
+    /// # }
+    /// ```
+    /// Note that in general the builder will use Options wrapping the actual type.
+    /// This is to help the builder know if the user has supplied a value for this
+    /// particular field.
+    fn get_builder_declaration(&self) -> TokenStream {
+        let name = &self.name;
+        let ty = self.storage_type();
+        quote!(
+            #name : std::option::Option<#ty>,
+        )
+    }
+
+    /// This function creates individual lines used to initialize the *Foo*Builder struct
+    /// when the user calls `Builder::builder()`. For example, if we have
+    /// ```rust
+    /// struct Foo {
+    ///     alpha: String,
+    ///     beta: Option<u8>,
+    ///     gamma: Vec<String>,
+    /// }
+    /// ```
+    /// then this function will generate one of the initialization lines for *Foo*Builder, like
+    /// ```rust
+    /// # struct Foo {
+    /// #     alpha: Option<String>,
+    /// # }
+    /// # fn t() -> Foo {
+    /// # Foo {
+    ///     alpha: None,
+    /// # }
+    /// # }
+    /// ```
+    /// Note that in general the builder will default to a `None` value, since the builder
+    /// wraps fields in an Option to ensure they have been provided.
+    fn get_builder_initializer(&self) -> TokenStream {
+        let name = &self.name;
+        if self.one_by_one_setter.is_some() {
+            quote!(
+                #name : std::option::Option::Some(std::vec::Vec::new()),
+            )
+        } else {
+            quote!(
+                #name : std::option::Option::None,
+            )
+        }
+    }
+
+    /// This function creates individual setter functions used to set values in the *Foo*Builder struct
+    /// when the user calls `Builder::setter()`. For example, if we have
+    /// ```rust
+    /// struct Foo {
+    ///     alpha: String,
+    ///     beta: Option<u8>,
+    ///     gamma: Vec<String>,
+    /// }
+    /// ```
+    /// then this function will generate one of the setter functions for *Foo*Builder, like
+    /// ```rust
+    /// # struct FooBuilder {
+    /// #     alpha: Option<String>,
+    /// # }
+    /// impl FooBuilder {
+    ///     pub fn alpha(&mut self, alpha: String) -> &mut Self {
+    ///         self.alpha = Some(alpha);
+    ///         self
+    ///     }
+    /// }
+    /// ```
+    /// If the field was also marked with `#[builder(each = baz)`, then the function will
+    /// include a setter for one-by-one setting.
+    ///
+    /// `Option` fields additionally get `maybe_beta(Option<u8>)`, which forwards an `Option` as
+    /// is, and `unset_beta()`, which forgets any earlier value as if no setter had been called.
+    /// With `#[builder(setter(strip_option = false))]` the main setter takes the `Option` itself
+    /// and `maybe_beta` is not generated.
+    fn get_builder_setter(&self) -> TokenStream {
+        let name = &self.name;
+        let ty = self.storage_type();
+        let it = &self.inner_type;
+
+        let mut q = quote!();
+
+        if self.parsed.is_some() {
+            return self.parsed.clone().unwrap();
+        }
+
+        if let Some(setter_name) = &self.one_by_one_setter {
+            // one by one
+            let it = it.clone().unwrap();
+            q.extend(quote!(
+                pub fn #setter_name (&mut self, value: #it) -> &mut Self {
+                    self.#name.get_or_insert_with(std::vec::Vec::new).push(value);
+                    self
+                }
+            ));
+        }
+
+        if self.one_by_one_setter.is_none() || &self.one_by_one_setter.clone().unwrap() != name {
+            if self.is_optional {
+                let it = self.inner_type.clone().unwrap();
+                let maybe = format_ident!("maybe_{}", name);
+                let unset = format_ident!("unset_{}", name);
+                if self.strip_option {
+                    q.extend(quote!(
+                        pub fn #name (&mut self, value: #it) -> &mut Self {
+                            self.#name = std::option::Option::Some(std::option::Option::Some(value));
+                            self
+                        }
+
+                        pub fn #maybe (&mut self, value: std::option::Option<#it>) -> &mut Self {
+                            self.#name = std::option::Option::Some(value);
+                            self
+                        }
+                    ));
+                } else {
+                    q.extend(quote!(
+                        pub fn #name (&mut self, value: std::option::Option<#it>) -> &mut Self {
+                            self.#name = std::option::Option::Some(value);
+                            self
+                        }
+                    ));
+                }
+                q.extend(quote!(
+                    pub fn #unset (&mut self) -> &mut Self {
+                        self.#name = std::option::Option::None;
+                        self
+                    }
+                ));
+            } else {
+                // normal setter
+                q.extend(quote!(
+                    pub fn #name (&mut self, value: #ty) -> &mut Self {
+                        self.#name = std::option::Option::Some(value);
+                        self
+                    }
+                ));
+            }
+        }
+
+        q
+    }
+
+    /// This function creates individual lines used to initialize the *Foo*Builder struct
+    /// when the user calls `Builder::build()`. For example, if we have
+    /// ```rust
+    /// struct Foo {
+    ///     alpha: String,
+    ///     beta: Option<u8>,
+    ///     gamma: Vec<String>,
+    /// }
+    /// ```
+    /// then this function will generate one of the initialization lines for `Builder`, like
+    /// ```rust
+    /// # struct Foo {
+    /// #     alpha: Option<String>,
+    /// # }
+    /// # fn t() -> Foo {
+    /// # Foo {
+    ///     alpha: None,
+    /// # }
+    /// # }
+    /// ```
+    /// If the field has custom storage, the built-up value is bound to the field's name and
+    /// the `build` expression given in `#[builder(field(...))]` produces the final value.
+    fn get_build_initializer(&self) -> TokenStream {
+        let name = &self.name;
+        let value = self.get_build_value();
+
+        quote!(
+            #name : #value,
+        )
+    }
+
+    /// This function creates the expression that moves this field's final value out of the
+    /// *Foo*Builder, as used by `get_build_initializer` and by the `call()` of function
+    /// builders. A required value that was never set, or was already moved out by an earlier
+    /// `call()`, panics with the field's name.
+    fn get_build_value(&self) -> TokenStream {
+        let name = &self.name;
+
+        let value = if self.is_optional {
+            quote!(self.#name.take().flatten())
+        } else if let Some(default) = &self.default {
+            quote!(self.#name.take().unwrap_or_else(|| #default))
+        } else if self.one_by_one_setter.is_some() {
+            // elements added since an earlier build start from an empty Vec
+            quote!(self.#name.take().unwrap_or_else(std::vec::Vec::new))
+        } else {
+            // move the value out, which `build()` checks was set beforehand while `call()`
+            // panics naming the argument
+            let missing = format!("`{name}` was not set");
+            quote!(self.#name.take().expect(#missing))
+        };
+
+        if let Some((_, build)) = &self.storage {
+            quote!({
+                let #name = #value;
+                #build
+            })
+        } else {
+            value
+        }
+    }
+
+    /// This function creates the owned `const fn` setter used by `#[builder(const)]` builders,
+    /// like
+    /// ```rust
+    /// # struct FooBuilder {
+    /// #     alpha: Option<u8>,
+    /// # }
+    /// impl FooBuilder {
+    ///     pub const fn alpha(mut self, value: u8) -> Self {
+    ///         self.alpha = Some(value);
+    ///         self
+    ///     }
+    /// }
+    /// ```
+    /// `Option` fields get the same `maybe_` and `unset_` setters as in `get_builder_setter`.
+    /// One-by-one setters cannot be `const`, so fields marked with `#[builder(each = ...)]`
+    /// produce a compile error instead.
+    fn get_const_setter(&self) -> TokenStream {
+        let name = &self.name;
+
+        if self.parsed.is_some() {
+            return self.parsed.clone().unwrap();
+        }
+
+        if self.one_by_one_setter.is_some() {
+            return syn::Error::new_spanned(name, "`each` is not supported by `builder(const)`")
+                .into_compile_error();
+        }
+
+        if self.is_optional {
+            let it = self.inner_type.clone().unwrap();
+            let maybe = format_ident!("maybe_{}", name);
+            let unset = format_ident!("unset_{}", name);
+            let mut q = if self.strip_option {
+                quote!(
+                    pub const fn #name (mut self, value: #it) -> Self {
+                        self.#name = std::option::Option::Some(std::option::Option::Some(value));
+                        self
+                    }
+
+                    pub const fn #maybe (mut self, value: std::option::Option<#it>) -> Self {
+                        self.#name = std::option::Option::Some(value);
+                        self
+                    }
+                )
+            } else {
+                quote!(
+                    pub const fn #name (mut self, value: std::option::Option<#it>) -> Self {
+                        self.#name = std::option::Option::Some(value);
+                        self
+                    }
+                )
+            };
+            q.extend(quote!(
+                pub const fn #unset (mut self) -> Self {
+                    self.#name = std::option::Option::None;
+                    self
+                }
+            ));
+            q
+        } else {
+            let ty = self.storage_type();
+            quote!(
+                pub const fn #name (mut self, value: #ty) -> Self {
+                    self.#name = std::option::Option::Some(value);
+                    self
+                }
+            )
+        }
+    }
+
+    /// This function creates the initialization line used by the `const fn build()` of
+    /// `#[builder(const)]` builders, where the builder fields have already been moved into
+    /// local bindings of the same name. A missing required field panics, which fails
+    /// compilation when the builder is evaluated in a `const` or `static` initializer.
+    /// `Default::default()` cannot be called there, so a `#[builder(default)]` without a value
+    /// produces a compile error instead.
+    fn get_const_build_initializer(&self) -> TokenStream {
+        let name = &self.name;
+        let missing = format!("`{name}` was not set");
+
+        let value = if let Some(bare) = &self.bare_default {
+            syn::Error::new_spanned(
+                bare,
+                r#"`default` without a value is not supported by `builder(const)`, give a constant like `default = "0"`"#,
+            )
+            .into_compile_error()
+        } else if self.is_optional {
+            quote!(match #name {
+                std::option::Option::Some(value) => value,
+                std::option::Option::None => std::option::Option::None,
+            })
+        } else if let Some(default) = &self.default {
+            quote!(match #name {
+                std::option::Option::Some(value) => value,
+                std::option::Option::None => #default,
+            })
+        } else {
+            quote!(match #name {
+                std::option::Option::Some(value) => value,
+                std::option::Option::None => std::panic!(#missing),
+            })
+        };
+
+        if let Some((_, build)) = &self.storage {
+            quote!(
+                #name : {
+                    let #name = #value;
+                    #build
+                },
+            )
+        } else {
+            quote!(
+                #name : #value,
+            )
+        }
+    }
+
+    /// This function creates the statement applying this field of a *Foo*Patch to an existing
+    /// value, for example
+    /// ```rust
+    /// # struct Foo { alpha: String }
+    /// # struct FooPatch { alpha: Option<String> }
+    /// # fn apply(patch: FooPatch, target: &mut Foo) {
+    /// # let this = patch;
+    ///     if let Some(alpha) = this.alpha {
+    ///         target.alpha = alpha;
+    ///     }
+    /// # }
+    /// ```
+    /// Fields with custom storage are converted with their `build` expression first. The field
+    /// is replaced as a whole, so `each` setters called on the patch replace the elements of a
+    /// `Vec` field rather than append to them, just as `diff()` records the whole `Vec`.
+    fn get_patch_apply(&self) -> TokenStream {
+        let name = &self.name;
+        let value = match &self.storage {
+            Some((_, build)) => quote!(#build),
+            None => quote!(#name),
+        };
+
+        quote!(
+            if let std::option::Option::Some(#name) = self.#name {
+                target.#name = #value;
+            }
+        )
+    }
+
+    /// This function creates the line of `Foo::diff()` recording this field of `other` in the
+    /// *Foo*Patch when it differs from `self`, which needs the field to be `PartialEq` and
+    /// `Clone`. Custom storage cannot be recovered from the field's value, so such fields are
+    /// never part of a diff, and neither are fields marked `#[builder(skip_diff)]`.
+    fn get_patch_diff(&self) -> TokenStream {
+        let name = &self.name;
+
+        if self.storage.is_some() || self.skip_diff {
+            return quote!(
+                #name : std::option::Option::None,
+            );
+        }
+
+        quote!(
+            #name : if self.#name != other.#name {
+                std::option::Option::Some(std::clone::Clone::clone(&other.#name))
+            } else {
+                std::option::Option::None
+            },
+        )
+    }
+
+    /// This function creates the match arm of `set_by_name()` for this field, which parses the
+    /// string with `FromStr` and stores it just like the setter would. For example
+    /// ```rust
+    /// # struct FooBuilder { alpha: Option<u8> }
+    /// # use derive_builder::SetError;
+    /// # fn set_by_name(this: &mut FooBuilder, name: &str, value: &str) -> Result<(), SetError> {
+    /// # match name {
+    ///     "alpha" => {
+    ///         let parsed = value.parse::<u8>().map_err(|e| SetError::Parse {
+    ///             field: "alpha",
+    ///             value: value.to_owned(),
+    ///             message: e.to_string(),
+    ///         })?;
+    ///         this.alpha = Some(parsed);
+    ///     }
+    /// # _ => {}
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    /// `Option` fields parse their inner type, and fields marked `#[builder(each = ...)]` parse
+    /// one element and push it.
+    fn get_set_by_name_arm(&self, krate: &syn::Path) -> TokenStream {
+        let name = &self.name;
+        let key = name.to_string();
+
+        let (parse_ty, store) = if self.one_by_one_setter.is_some() {
+            (
+                self.inner_type.clone().unwrap(),
+                quote!(self.#name.get_or_insert_with(std::vec::Vec::new).push(parsed);),
+            )
+        } else if self.is_optional {
+            (
+                self.inner_type.clone().unwrap(),
+                quote!(self.#name = std::option::Option::Some(std::option::Option::Some(parsed));),
+            )
+        } else {
+            (
+                self.storage_type().clone(),
+                quote!(self.#name = std::option::Option::Some(parsed);),
+            )
+        };
+
+        quote!(
+            #key => {
+                let parsed = <#parse_ty as std::str::FromStr>::from_str(value).map_err(|e| {
+                    #krate::SetError::Parse {
+                        field: #key,
+                        value: std::borrow::ToOwned::to_owned(value),
+                        message: std::string::ToString::to_string(&e),
+                    }
+                })?;
+                #store
+            }
+        )
+    }
+
+    /// This function creates the entry of `Foo::BUILDER_FIELDS` describing this field, like
+    /// ```rust
+    /// # use derive_builder::{FieldInfo, FieldKind};
+    /// # const F: FieldInfo =
+    /// FieldInfo {
+    ///     name: "beta",
+    ///     ty: "Option<u8>",
+    ///     kind: FieldKind::Optional,
+    ///     doc: "How many betas there are",
+    /// }
+    /// # ;
+    /// ```
+    fn get_field_info(&self, krate: &syn::Path) -> TokenStream {
+        let name = self.name.to_string();
+        let ty = type_name(&self.ty);
+        let doc = &self.doc;
+
+        let kind = if self.is_required() {
+            quote!(Required)
+        } else if self.is_optional {
+            quote!(Optional)
+        } else if let Some(each) = &self.one_by_one_setter {
+            let each = each.to_string();
+            quote!(Repeated { each: #each })
+        } else {
+            quote!(Defaulted)
+        };
+
+        quote!(
+            #krate::FieldInfo {
+                name: #name,
+                ty: #ty,
+                kind: #krate::FieldKind::#kind,
+                doc: #doc,
+            },
+        )
+    }
+}
+
+fn create_builder_struct(
+    vis: &Visibility,
+    builder_name: &Ident,
+    fields: &Vec<AnnotatedField>,
+) -> TokenStream {
+    let mut field_defs = quote!();
+    for field in fields {
+        field_defs.extend(field.get_builder_declaration());
+    }
+
+    quote!(
+        #vis struct #builder_name {
+            #field_defs
+        }
+    )
+}
+
+fn create_builder_function(
+    target_type: &Ident,
+    builder_type: &Ident,
+    fields: &Vec<AnnotatedField>,
+    is_const: bool,
+) -> TokenStream {
+    let mut initializers = quote!();
+    for field in fields {
+        initializers.extend(field.get_builder_initializer());
+    }
+    let constness = if is_const { quote!(const) } else { quote!() };
+
+    quote!(
+        impl #target_type {
+            pub #constness fn builder() -> #builder_type {
+                #builder_type {
+                    #initializers
+                }
+            }
+        }
+    )
+}
+
+fn create_setter_fns(builder_type: &Ident, fields: &Vec<AnnotatedField>) -> TokenStream {
+    let mut setters = quote!();
+    for field in fields {
+        setters.extend(field.get_builder_setter());
+    }
+
+    quote!(
+        impl #builder_type {
+            #setters
+        }
+    )
+}
+
+/// Creates the condition under which every required field of the builder `this` has been set.
+fn create_required_check(this: TokenStream, fields: &[AnnotatedField]) -> TokenStream {
+    let required = fields.iter().filter(|f| f.is_required()).map(|f| &f.name);
+
+    quote!(true #(&& #this.#required.is_some())*)
+}
+
+fn create_build_fn(
+    target_type: &Ident,
+    builder_type: &Ident,
+    fields: &Vec<AnnotatedField>,
+) -> TokenStream {
+    let mut initializers = quote!();
+    for field in fields {
+        initializers.extend(field.get_build_initializer());
+    }
+    let is_complete = create_required_check(quote!(self), fields);
+
+    // check every required field before taking any, so that a failed build leaves the builder
+    // as it was
+    quote!(
+        impl #builder_type {
+            pub fn build(&mut self) -> std::option::Option<#target_type> {
+                if !(#is_complete) {
+                    return std::option::Option::None;
+                }
+                std::option::Option::Some( #target_type {
+                    #initializers
+                } )
+            }
+        }
+    )
+}
+
+fn create_const_setter_fns(builder_type: &Ident, fields: &Vec<AnnotatedField>) -> TokenStream {
+    let mut setters = quote!();
+    for field in fields {
+        setters.extend(field.get_const_setter());
+    }
+
+    quote!(
+        impl #builder_type {
+            #setters
+        }
+    )
+}
+
+fn create_const_build_fn(
+    target_type: &Ident,
+    builder_type: &Ident,
+    fields: &Vec<AnnotatedField>,
+) -> TokenStream {
+    let names = fields.iter().map(|f| &f.name);
+    let mut initializers = quote!();
+    for field in fields {
+        initializers.extend(field.get_const_build_initializer());
+    }
+
+    quote!(
+        impl #builder_type {
+            pub const fn build(self) -> #target_type {
+                let #builder_type { #(#names,)* } = self;
+                #target_type {
+                    #initializers
+                }
+            }
+        }
+    )
+}
+
+fn create_patch(
+    vis: &Visibility,
+    target_type: &Ident,
+    fields: &Vec<AnnotatedField>,
+) -> TokenStream {
+    let patch_type = format_ident!("{target_type}Patch");
+    let mut field_defs = quote!();
+    let mut setters = quote!();
+    let mut applies = quote!();
+    let mut diffs = quote!();
+    for field in fields {
+        field_defs.extend(field.get_builder_declaration());
+        setters.extend(field.get_builder_setter());
+        applies.extend(field.get_patch_apply());
+        diffs.extend(field.get_patch_diff());
+    }
+
+    quote!(
+        #[derive(Default)]
+        #vis struct #patch_type {
+            #field_defs
+        }
+
+        impl #patch_type {
+            #setters
+
+            pub fn apply(self, target: &mut #target_type) {
+                #applies
+            }
+        }
+
+        impl #target_type {
+            pub fn diff(&self, other: &#target_type) -> #patch_type {
+                #patch_type {
+                    #diffs
+                }
+            }
+        }
+    )
+}
+
+fn create_set_by_name_fns(
+    krate: &syn::Path,
+    target_type: &Ident,
+    builder_type: &Ident,
+    fields: &[AnnotatedField],
+) -> TokenStream {
+    let fields: Vec<_> = fields.iter().filter(|f| !f.skip_set_by_name).collect();
+    let mut arms = quote!();
+    for field in &fields {
+        arms.extend(field.get_set_by_name_arm(krate));
+    }
+    let keys = fields.iter().map(|f| f.name.to_string());
+    let repeated = fields.iter().map(|f| f.one_by_one_setter.is_some());
+
+    quote!(
+        impl #builder_type {
+            pub fn set_by_name(
+                &mut self,
+                name: &str,
+                value: &str,
+            ) -> std::result::Result<(), #krate::SetError> {
+                match name {
+                    #arms
+                    _ => {
+                        return std::result::Result::Err(#krate::SetError::UnknownField(
+                            std::borrow::ToOwned::to_owned(name),
+                        ))
+                    }
+                }
+                std::result::Result::Ok(())
+            }
+
+            pub fn from_env(prefix: &str) -> std::result::Result<Self, #krate::SetError> {
+                let mut builder = #target_type::builder();
+                for (key, repeated) in [#((#keys, #repeated)),*] {
+                    let var = std::format!("{}_{}", prefix, key.to_uppercase());
+                    match std::env::var(&var) {
+                        // `each` fields take a comma-separated list of elements
+                        std::result::Result::Ok(value) if repeated => {
+                            for element in value.split(',').filter(|e| !e.is_empty()) {
+                                builder.set_by_name(key, element)?;
+                            }
+                        }
+                        std::result::Result::Ok(value) => builder.set_by_name(key, &value)?,
+                        std::result::Result::Err(std::env::VarError::NotPresent) => {}
+                        std::result::Result::Err(std::env::VarError::NotUnicode(_)) => {
+                            return std::result::Result::Err(#krate::SetError::NotUnicode {
+                                field: key,
+                                var,
+                            })
+                        }
+                    }
+                }
+                std::result::Result::Ok(builder)
+            }
+        }
+    )
+}
+
+fn create_field_infos(
+    krate: &syn::Path,
+    target_type: &Ident,
+    fields: &Vec<AnnotatedField>,
+) -> TokenStream {
+    let mut infos = quote!();
+    for field in fields {
+        infos.extend(field.get_field_info(krate));
+    }
+
+    quote!(
+        impl #target_type {
+            pub const BUILDER_FIELDS: &'static [#krate::FieldInfo] = &[
+                #infos
+            ];
+        }
+    )
+}
+
+fn create_trait_impls(
+    krate: &syn::Path,
+    target_type: &Ident,
+    builder_type: &Ident,
+    fields: &[AnnotatedField],
+    is_const: bool,
+) -> TokenStream {
+    // const builders are owned, so build from a fresh builder swapped in for this one, once
+    // the `build()` that panics on missing fields is known to succeed
+    let build = if is_const {
+        let is_complete = create_required_check(quote!(self), fields);
+        quote!(
+            if !(#is_complete) {
+                return std::result::Result::Err(#krate::BuildError);
+            }
+            std::result::Result::Ok(std::mem::replace(self, #target_type::builder()).build())
+        )
+    } else {
+        quote!(#builder_type::build(self).ok_or(#krate::BuildError))
+    };
+
+    quote!(
+        impl #krate::Buildable for #target_type {
+            type Builder = #builder_type;
+
+            fn builder() -> #builder_type {
+                #target_type::builder()
+            }
+        }
+
+        impl #krate::Builder for #builder_type {
+            type Output = #target_type;
+            type Error = #krate::BuildError;
+
+            fn build(&mut self) -> std::result::Result<#target_type, #krate::BuildError> {
+                #build
+            }
+        }
+    )
+}
+
+/// Expands `#[derive(Builder)]` for the given struct, returning the builder, its setters and
+/// every other item requested through `#[builder(...)]` attributes. Errors are reported as
+/// `compile_error!` invocations in the returned tokens.
+#[allow(clippy::missing_panics_doc, clippy::too_many_lines)]
+pub fn derive(derive_input: &DeriveInput) -> TokenStream {
+    let mut res = TokenStream::new();
+
+    //eprintln!("Derive input is: {derive_input:#?}");
+
+    let struct_attrs = match get_struct_attrs(derive_input) {
+        Ok(attrs) => attrs,
+        Err(ts) => return ts,
+    };
+    let krate = struct_attrs.crate_path();
+
+    let struct_name = derive_input.ident.clone();
+    let builder = Ident::new(&format!("{struct_name}Builder"), Span::call_site());
+
+    let mut annotated_fields: Vec<AnnotatedField> = vec![];
+    #[allow(clippy::single_match)]
+    match &derive_input.data {
+        syn::Data::Struct(data) => match &data.fields {
+            syn::Fields::Named(fields) => {
+                for f in &fields.named {
+                    annotated_fields.push(f.into());
+                }
+            }
+            _ => (),
+        },
+        _ => (),
+    }
+
+    // create TypeBuilder struct
+    // with the struct's visibility, since it is named by the Buildable impl
+    res.extend(create_builder_struct(
+        &derive_input.vis,
+        &builder,
+        &annotated_fields,
+    ));
+
+    // create builder fn
+    res.extend(create_builder_function(
+        &struct_name,
+        &builder,
+        &annotated_fields,
+        struct_attrs.is_const,
+    ));
+
+    if struct_attrs.is_const {
+        // create owned const setter functions and const build fn
+        res.extend(create_const_setter_fns(&builder, &annotated_fields));
+        res.extend(create_const_build_fn(
+            &struct_name,
+            &builder,
+            &annotated_fields,
+        ));
+    } else {
+        // create setter functions in original struct
+        res.extend(create_setter_fns(&builder, &annotated_fields));
+
+        // create build fn
+        res.extend(create_build_fn(&struct_name, &builder, &annotated_fields));
+    }
+
+    // connect Foo and FooBuilder through the derive_builder traits
+    res.extend(create_trait_impls(
+        &krate,
+        &struct_name,
+        &builder,
+        &annotated_fields,
+        struct_attrs.is_const,
+    ));
+
+    // create Foo::BUILDER_FIELDS
+    res.extend(create_field_infos(&krate, &struct_name, &annotated_fields));
+
+    if struct_attrs.set_by_name {
+        // create set_by_name and from_env on the builder
+        res.extend(create_set_by_name_fns(
+            &krate,
+            &struct_name,
+            &builder,
+            &annotated_fields,
+        ));
+    }
+
+    if struct_attrs.patch {
+        // create FooPatch with the same setters as the builder
+        res.extend(create_patch(
+            &derive_input.vis,
+            &struct_name,
+            &annotated_fields,
+        ));
+    }
+
+    res
+}
+
+/// Expands the `#[builder]` attribute with arguments `args` applied to the function or `impl`
+/// block `input`. Errors are reported as `compile_error!` invocations in the returned tokens.
+pub fn builder(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new_spanned(args, "expected `#[builder]`").into_compile_error();
+    }
+
+    let expanded = match syn::parse2::<Item>(input) {
+        Ok(Item::Fn(item)) => create_fn_builder(item),
+        Ok(Item::Impl(item)) => create_method_builders(item),
+        Ok(item) => Err(syn::Error::new_spanned(
+            item,
+            "`#[builder]` expects a function or an `impl` block",
+        )),
+        Err(e) => Err(e),
+    };
+
+    match expanded {
+        Ok(ts) => ts,
+        Err(e) => e.into_compile_error(),
+    }
+}
+
+/// The pieces generated for one function marked `#[builder]`: the function callers invoke to
+/// get a builder, and the builder struct with its setters and `call()`.
+struct CallBuilder {
+    entry: TokenStream,
+    items: TokenStream,
+}
+
+fn create_fn_builder(mut item: ItemFn) -> syn::Result<TokenStream> {
+    let builder_type = format_ident!("{}Builder", to_upper_camel(&item.sig.ident));
+    let vis = std::mem::replace(&mut item.vis, Visibility::Inherited);
+    let attrs = split_fn_attrs(&mut item.attrs);
+    let args = strip_arg_attrs(&mut item.sig)?;
+
+    // the original function lives on inside `call()`, where it shadows the entry point
+    let name = &item.sig.ident;
+    let target = quote!(#item #name);
+    let CallBuilder { entry, items } = create_call_builder(
+        None,
+        &vis,
+        &item.sig,
+        &builder_type,
+        &args,
+        &attrs,
+        target,
+    )?;
+
+    Ok(quote!(
+        #entry
+        #items
+    ))
+}
+
+fn create_method_builders(mut item: ItemImpl) -> syn::Result<TokenStream> {
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "`#[builder]` does not support generic `impl` blocks",
+        ));
+    }
+    let self_ty = item.self_ty.clone();
+    let self_name = match &*self_ty {
+        Type::Path(path) if path.qself.is_none() => &path.path.segments.last().unwrap().ident,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                self_ty,
+                "`#[builder]` expects an `impl` block for a named type",
+            ))
+        }
+    };
+
+    let mut entries = vec![];
+    let mut builders = quote!();
+    for impl_item in &mut item.items {
+        let ImplItem::Fn(method) = impl_item else {
+            continue;
+        };
+        let Some(pos) = method
+            .attrs
+            .iter()
+            .position(|a| a.path().is_ident("builder"))
+        else {
+            continue;
+        };
+        method.attrs.remove(pos);
+
+        // the original method stays in the impl block under a hidden name
+        let name = method.sig.ident.clone();
+        let builder_type = format_ident!("{}{}Builder", self_name, to_upper_camel(&name));
+        let hidden = format_ident!("__builder_{}", name);
+        let vis = std::mem::replace(&mut method.vis, Visibility::Inherited);
+        let attrs = split_fn_attrs(&mut method.attrs);
+        // `Self` in the signature would mean the builder once copied into it
+        let mut sig = method.sig.clone();
+        ReplaceSelf { self_ty: &self_ty }.visit_signature_mut(&mut sig);
+        let args = strip_arg_attrs(&mut sig)?;
+        for arg in &mut method.sig.inputs {
+            if let FnArg::Typed(arg) = arg {
+                arg.attrs.retain(|a| !a.path().is_ident("builder"));
+            }
+        }
+        let target = if method.sig.receiver().is_some() {
+            quote!(self.__receiver.#hidden)
+        } else {
+            quote!(<#self_ty>::#hidden)
+        };
+        let CallBuilder { entry, items } = create_call_builder(
+            Some(&self_ty),
+            &vis,
+            &sig,
+            &builder_type,
+            &args,
+            &attrs,
+            target,
+        )?;
+        method.sig.ident = hidden;
+        method.attrs.push(syn::parse_quote!(#[doc(hidden)]));
+        entries.push(ImplItem::Verbatim(entry));
+        builders.extend(items);
+    }
+    item.items.extend(entries);
+
+    Ok(quote!(
+        #item
+        #builders
+    ))
+}
+
+/// The outer attributes of a function marked `#[builder]`, sorted by the generated item they
+/// belong on.
+#[derive(Default)]
+struct FnAttrs {
+    /// `doc` and `deprecated`, which describe the entry point that callers invoke
+    entry: Vec<Attribute>,
+    /// `must_use`, which concerns the result returned by `call()`
+    call: Vec<Attribute>,
+    /// `cfg`, which applies to the builder as much as to the function itself
+    cfg: Vec<Attribute>,
+}
+
+/// Moves the attributes of a function marked `#[builder]` that belong on the generated items
+/// out of `attrs`. `cfg` attributes are copied instead, and everything else, like `inline`
+/// or `allow`, stays with the original function.
+fn split_fn_attrs(attrs: &mut Vec<Attribute>) -> FnAttrs {
+    let mut fn_attrs = FnAttrs::default();
+    attrs.retain(|a| {
+        let path = a.path();
+        if path.is_ident("doc") || path.is_ident("deprecated") {
+            fn_attrs.entry.push(a.clone());
+            false
+        } else if path.is_ident("must_use") {
+            fn_attrs.call.push(a.clone());
+            false
+        } else {
+            if path.is_ident("cfg") {
+                fn_attrs.cfg.push(a.clone());
+            }
+            true
+        }
+    });
+    fn_attrs
+}
+
+/// The typed arguments of a function marked `#[builder]`.
+struct FnArgs {
+    /// The arguments, classified like the fields of `#[derive(Builder)]`
+    fields: Vec<AnnotatedField>,
+    /// The names given to the lifetimes elided in the argument types
+    elided: Vec<Lifetime>,
+}
+
+/// Replaces `Self` in the signature of a method by the type of its `impl` block, for use
+/// outside of that block.
+struct ReplaceSelf<'a> {
+    self_ty: &'a Type,
+}
+
+impl VisitMut for ReplaceSelf<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        match ty {
+            Type::Path(path) if path.qself.is_none() && path.path.is_ident("Self") => {
+                *ty = self.self_ty.clone();
+            }
+            _ => visit_mut::visit_type_mut(self, ty),
+        }
+    }
+
+    // `&self` and `&mut self` keep their shorthand
+    fn visit_receiver_mut(&mut self, _: &mut syn::Receiver) {}
+}
+
+/// Names the lifetimes elided in the type of an argument, like `&str` or `Cow<'_, str>`, so
+/// that the builder holding the argument can declare them.
+#[derive(Default)]
+struct NameElidedLifetimes {
+    /// The lifetimes given to elided ones so far, `'__arg0`, `'__arg1` and so on
+    named: Vec<Lifetime>,
+    /// The first `impl Trait` argument found, which the builder has no type to store in
+    impl_trait: Option<TypeImplTrait>,
+}
+
+impl NameElidedLifetimes {
+    fn next(&mut self, span: Span) -> Lifetime {
+        let lifetime = Lifetime::new(&format!("'__arg{}", self.named.len()), span);
+        self.named.push(lifetime.clone());
+        lifetime
+    }
+}
+
+impl VisitMut for NameElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(self.next(reference.and_token.span));
+        }
+        visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.next(lifetime.span());
+        }
+    }
+
+    fn visit_type_impl_trait_mut(&mut self, impl_trait: &mut TypeImplTrait) {
+        self.impl_trait.get_or_insert_with(|| impl_trait.clone());
+    }
+
+    // lifetimes elided in `fn(&str)` and `Fn(&str)` are higher-ranked, not taken from the
+    // surrounding function
+    fn visit_type_bare_fn_mut(&mut self, _: &mut syn::TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(
+        &mut self,
+        _: &mut syn::ParenthesizedGenericArguments,
+    ) {
+    }
+}
+
+/// Classifies the typed arguments of `sig`, removing the `#[builder(...)]` attributes that
+/// the compiler would otherwise reject. Lifetimes elided in the argument types are given
+/// names in the classified types.
+fn strip_arg_attrs(sig: &mut Signature) -> syn::Result<FnArgs> {
+    let mut elided = NameElidedLifetimes::default();
+    let mut fields = vec![];
+    for arg in &mut sig.inputs {
+        let FnArg::Typed(arg) = arg else {
+            continue;
+        };
+        let Pat::Ident(pat) = &*arg.pat else {
+            return Err(syn::Error::new_spanned(
+                &arg.pat,
+                "`#[builder]` arguments must be plain identifiers",
+            ));
+        };
+        let (builder_attrs, attrs): (Vec<Attribute>, Vec<Attribute>) = arg
+            .attrs
+            .drain(..)
+            .partition(|a| a.path().is_ident("builder"));
+        arg.attrs = attrs;
+        let mut ty = (*arg.ty).clone();
+        elided.visit_type_mut(&mut ty);
+        fields.push(AnnotatedField::new(pat.ident.clone(), ty, &builder_attrs));
+    }
+    if let Some(impl_trait) = elided.impl_trait {
+        return Err(syn::Error::new_spanned(
+            impl_trait,
+            "`#[builder]` does not support `impl Trait` arguments, use a type parameter instead",
+        ));
+    }
+    Ok(FnArgs {
+        fields,
+        elided: elided.named,
+    })
+}
+
+/// Generates the builder for a single function. `self_ty` is the type of the surrounding
+/// `impl` block, if any, `attrs` the function's attributes meant for the generated items, and
+/// `target` is the path `call()` invokes with the built arguments.
+///
+/// The builder is generic over the function's generic parameters and the named elided
+/// lifetimes, plus the lifetime of the receiver for methods taking `&self` or `&mut self`.
+fn create_call_builder(
+    self_ty: Option<&Type>,
+    vis: &Visibility,
+    sig: &Signature,
+    builder_type: &Ident,
+    args: &FnArgs,
+    attrs: &FnAttrs,
+    target: TokenStream,
+) -> syn::Result<CallBuilder> {
+    let FnArgs { fields, elided } = args;
+    let FnAttrs {
+        entry: entry_attrs,
+        call: call_attrs,
+        cfg,
+    } = attrs;
+    let name = &sig.ident;
+    let output = &sig.output;
+    let asyncness = &sig.asyncness;
+    let dot_await = asyncness.map(|_| quote!(.await));
+
+    // methods keep a reference to their receiver in the builder
+    let (receiver_decl, receiver_init, entry_receiver) = match sig.receiver() {
+        None => (quote!(), quote!(), quote!()),
+        Some(receiver) => {
+            let mutability = match receiver.reference {
+                Some((_, None)) => &receiver.mutability,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        receiver,
+                        "`#[builder]` methods must take `&self` or `&mut self`",
+                    ))
+                }
+            };
+            (
+                quote!(__receiver: &'__receiver #mutability #self_ty,),
+                quote!(__receiver: self,),
+                quote!(& #mutability self),
+            )
+        }
+    };
+
+    // the entry point declares the function's generics and the elided lifetimes, which come
+    // after the declared lifetimes
+    let mut entry_generics = sig.generics.clone();
+    let lifetimes = entry_generics.lifetimes().count();
+    for (i, lifetime) in elided.iter().enumerate() {
+        let param = GenericParam::Lifetime(LifetimeParam::new(lifetime.clone()));
+        entry_generics.params.insert(lifetimes + i, param);
+    }
+    let mut generics = entry_generics.clone();
+    let mut entry_args = vec![];
+    if sig.receiver().is_some() {
+        generics.params.insert(0, syn::parse_quote!('__receiver));
+        entry_args.push(quote!('_));
+    }
+    for param in &entry_generics.params {
+        entry_args.push(match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote!(#lifetime)
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+        });
+    }
+    let entry_args = if entry_args.is_empty() {
+        quote!()
+    } else {
+        quote!(<#(#entry_args),*>)
+    };
+    let (entry_impl_generics, _, where_clause) = entry_generics.split_for_impl();
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    // parameters only used by the return type still need to appear in the builder
+    let phantom_lifetimes: Vec<_> = generics.lifetimes().map(|p| &p.lifetime).collect();
+    let phantom_types: Vec<_> = generics.type_params().map(|p| &p.ident).collect();
+    let (phantom_decl, phantom_init) = if phantom_lifetimes.is_empty() && phantom_types.is_empty() {
+        (quote!(), quote!())
+    } else {
+        (
+            quote!(__phantom: std::marker::PhantomData<(
+                #(&#phantom_lifetimes (),)*
+                #(fn() -> #phantom_types,)*
+            )>,),
+            quote!(__phantom: std::marker::PhantomData,),
+        )
+    };
+
+    // type parameters may not be inferable from the arguments alone
+    let explicit: Vec<_> = sig
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            GenericParam::Const(param) => Some(&param.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    let turbofish = if explicit.is_empty() {
+        quote!()
+    } else {
+        quote!(::<#(#explicit),*>)
+    };
+
+    let mut decls = quote!();
+    let mut inits = quote!();
+    let mut setters = quote!();
+    let mut moves = quote!();
+    for field in fields {
+        let arg = &field.name;
+        let value = field.get_build_value();
+        decls.extend(field.get_builder_declaration());
+        inits.extend(field.get_builder_initializer());
+        setters.extend(field.get_builder_setter());
+        moves.extend(quote!(
+            let #arg = #value;
+        ));
+    }
+    let arg_names = fields.iter().map(|f| &f.name);
+
+    Ok(CallBuilder {
+        entry: quote!(
+            #(#entry_attrs)*
+            #(#cfg)*
+            #vis fn #name #entry_impl_generics (#entry_receiver) -> #builder_type #entry_args
+            #where_clause
+            {
+                #builder_type {
+                    #receiver_init
+                    #phantom_init
+                    #inits
+                }
+            }
+        ),
+        items: quote!(
+            #(#cfg)*
+            #vis struct #builder_type #impl_generics #where_clause {
+                #receiver_decl
+                #phantom_decl
+                #decls
+            }
+
+            #(#cfg)*
+            impl #impl_generics #builder_type #ty_generics #where_clause {
+                #setters
+
+                #(#call_attrs)*
+                pub #asyncness fn call(&mut self) #output {
+                    #moves
+                    #target #turbofish (#(#arg_names),*) #dot_await
+                }
+            }
+        ),
+    })
+}
+
+/// Converts a `snake_case` function name into the `UpperCamelCase` used for its builder type.
+fn to_upper_camel(ident: &Ident) -> String {
+    ident
+        .to_string()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_uppercase();
+            first.chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+fn get_struct_attrs(input: &DeriveInput) -> Result<StructAttrs, TokenStream> {
+    let mut attrs = StructAttrs::default();
+    for a in &input.attrs {
+        if !a.path().is_ident("builder") {
+            continue;
+        }
+        a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("const") {
+                attrs.is_const = true;
+                Ok(())
+            } else if meta.path.is_ident("patch") {
+                attrs.patch = true;
+                Ok(())
+            } else if meta.path.is_ident("set_by_name") {
+                attrs.set_by_name = true;
+                Ok(())
+            } else if meta.path.is_ident("crate") {
+                let lstr: LitStr = meta.value()?.parse()?;
+                attrs.crate_path = Some(lstr.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "expected `builder(const)`, `builder(patch)`, `builder(set_by_name)` or `builder(crate = \"...\")`",
+                ))
+            }
+        })
+        .map_err(|e| e.into_compile_error())?;
+    }
+    Ok(attrs)
+}
+
+fn get_builder_attrs(field_attrs: &[Attribute]) -> Result<FieldAttrs, TokenStream> {
+    let mut attrs = FieldAttrs::default();
+    for a in field_attrs {
+        if !a.path().is_ident("builder") {
+            continue;
+        }
+        a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("each") {
+                let lstr: LitStr = meta.value()?.parse()?;
+                let s = lstr.value();
+                attrs.each = Some(Ident::new(&s, Span::call_site()));
+                Ok(())
+            } else if meta.path.is_ident("setter") {
+                meta.parse_nested_meta(|inner| {
+                    if inner.path.is_ident("strip_option") {
+                        attrs.strip_option = Some(inner.value()?.parse()?);
+                        Ok(())
+                    } else {
+                        Err(inner.error("expected `strip_option = ...`"))
+                    }
+                })
+            } else if meta.path.is_ident("default") {
+                attrs.default = Some(if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<LitStr>()?.parse()?
+                } else {
+                    attrs.bare_default = Some(meta.path.clone());
+                    syn::parse_quote!(std::default::Default::default())
+                });
+                Ok(())
+            } else if meta.path.is_ident("field") {
+                let mut ty = None;
+                let mut build = None;
+                meta.parse_nested_meta(|inner| {
+                    let lstr: LitStr = inner.value()?.parse()?;
+                    if inner.path.is_ident("ty") {
+                        ty = Some(lstr.parse::<Type>()?);
+                    } else if inner.path.is_ident("build") {
+                        build = Some(lstr.parse::<Expr>()?);
+                    } else {
+                        return Err(inner.error(r#"expected `ty = "..."` or `build = "..."`"#));
+                    }
+                    Ok(())
+                })?;
+                match (ty, build) {
+                    (Some(ty), Some(build)) => {
+                        attrs.storage = Some((ty, build));
+                        Ok(())
+                    }
+                    _ => Err(meta.error(r#"expected `field(ty = "...", build = "...")`"#)),
+                }
+            } else if meta.path.is_ident("skip_diff") {
+                attrs.skip_diff = true;
+                Ok(())
+            } else if meta.path.is_ident("skip_set_by_name") {
+                attrs.skip_set_by_name = true;
+                Ok(())
+            } else {
+                Err(syn::Error::new_spanned(
+                    &a.meta,
+                    indoc! {r#"expected `builder(each = "...")`"#},
+                ))
+            }
+        })
+        .map_err(|e| e.into_compile_error())?;
+    }
+    Ok(attrs)
+}
+
+fn get_doc(attrs: &[Attribute]) -> String {
+    let mut lines = vec![];
+    for a in attrs {
+        if !a.path().is_ident("doc") {
+            continue;
+        }
+        if let syn::Meta::NameValue(mnv) = &a.meta {
+            if let Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lstr),
+                ..
+            }) = &mnv.value
+            {
+                let line = lstr.value();
+                lines.push(line.strip_prefix(' ').unwrap_or(&line).to_owned());
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Renders a type the way it would usually be written, like `Option<String>`, `[u8; 4]` or
+/// `Box<dyn Fn(&str) -> u8 + Send>`, rather than with a space between every two tokens as
+/// produced by printing them.
+fn type_name(typ: &syn::Type) -> String {
+    let mut name = String::new();
+    write_tokens(&mut name, quote!(#typ));
+    name
+}
+
+fn write_tokens(out: &mut String, tokens: TokenStream) {
+    let mut prev: Option<TokenTree> = None;
+    // whether `prev` is the second `:` of a `::`
+    let mut path_sep = false;
+    for tt in tokens {
+        if let Some(prev) = &prev {
+            if needs_space(prev, &tt, path_sep) {
+                out.push(' ');
+            }
+            path_sep = matches!((prev, &tt), (TokenTree::Punct(a), TokenTree::Punct(b))
+                if a.as_char() == ':' && a.spacing() == Spacing::Joint && b.as_char() == ':');
+        }
+        match &tt {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{ ", " }"),
+                    Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                write_tokens(out, group.stream());
+                out.push_str(close);
+            }
+            _ => out.push_str(&tt.to_string()),
+        }
+        prev = Some(tt);
+    }
+}
+
+/// Whether a type is written with a space between the tokens `prev` and `next`.
+fn needs_space(prev: &TokenTree, next: &TokenTree, path_sep: bool) -> bool {
+    if let TokenTree::Punct(punct) = prev {
+        // the `'` of a lifetime is joint too
+        if punct.spacing() == Spacing::Joint || matches!(punct.as_char(), '<' | '&' | '*') {
+            return false;
+        }
+        if punct.as_char() == ':' && path_sep {
+            return false;
+        }
+    }
+    match next {
+        TokenTree::Punct(punct) => !matches!(punct.as_char(), ',' | ';' | ':' | '<' | '>'),
+        // the arguments of `fn(u8)` and `Fn(&str)`
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Parenthesis => {
+            !matches!(prev, TokenTree::Ident(_))
+        }
+        _ => true,
+    }
+}
+
+fn get_option_type(typ: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(path) = typ {
+        #[allow(clippy::collapsible_if)]
+        if path.qself.is_none() {
+            // only one thing inside the Option (Option takes a single generic argument)
+            if path.path.segments.len() == 1 {
+                let segment = path
+                    .path
+                    .segments
+                    .first()
+                    .expect("path segments has a segment");
+                let ident = &segment.ident;
+                // are we an Option?
+                if ident == &Ident::new("Option", Span::call_site()) {
+                    if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                        let a = args.args.first().expect("args has a generic argument");
+                        match a {
+                            syn::GenericArgument::Type(t) => {
+                                return Some(t);
+                            }
+                            _ => unimplemented!(),
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn get_vec_type(typ: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(path) = typ {
+        #[allow(clippy::collapsible_if)]
+        if path.qself.is_none() {
+            // only one thing inside the Vec (Vec takes a single generic argument)
+            if path.path.segments.len() == 1 {
+                let segment = path
+                    .path
+                    .segments
+                    .first()
+                    .expect("path segments has a segment");
+                let ident = &segment.ident;
+                // are we an Vec?
+                if ident == &Ident::new("Vec", Span::call_site()) {
+                    if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                        let a = args.args.first().expect("args has a generic argument");
+                        match a {
+                            syn::GenericArgument::Type(t) => {
+                                return Some(t);
+                            }
+                            _ => unimplemented!(),
+                        }
+                    }
+                } else {
+                    panic!("Did not have a vec!");
+                }
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,69 @@
+use quote::quote;
+use syn::{parse_quote, DeriveInput, File, Item};
+
+fn struct_names(file: &File) -> Vec<String> {
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Struct(item) => Some(item.ident.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn derive_expands_to_builder() {
+    let input: DeriveInput = parse_quote! {
+        #[builder(patch)]
+        pub struct Command {
+            executable: String,
+            #[builder(each = "arg")]
+            args: Vec<String>,
+            current_dir: Option<String>,
+        }
+    };
+
+    let file: File = syn::parse2(derive_builder_core::derive(&input)).unwrap();
+    assert_eq!(struct_names(&file), ["CommandBuilder", "CommandPatch"]);
+}
+
+#[test]
+fn derive_reports_unrecognized_attribute() {
+    let input: DeriveInput = parse_quote! {
+        pub struct Command {
+            #[builder(eac = "arg")]
+            args: Vec<String>,
+        }
+    };
+
+    let expanded = derive_builder_core::derive(&input).to_string();
+    assert!(expanded.contains("compile_error"));
+    assert!(expanded.contains(r#"expected `builder(each = \"...\")`"#));
+}
+
+#[test]
+fn builder_attribute_expands_function() {
+    let expanded = derive_builder_core::builder(
+        quote!(),
+        quote! {
+            pub fn connect(host: String, port: Option<u16>) -> String {
+                format!("{host}:{}", port.unwrap_or(80))
+            }
+        },
+    );
+
+    let file: File = syn::parse2(expanded).unwrap();
+    assert_eq!(struct_names(&file), ["ConnectBuilder"]);
+    assert!(matches!(&file.items[0], Item::Fn(f) if f.sig.ident == "connect"));
+}
+
+#[test]
+fn builder_attribute_rejects_arguments() {
+    let expanded = derive_builder_core::builder(
+        quote!(patch),
+        quote!(
+            fn f() {}
+        ),
+    );
+    assert!(expanded.to_string().contains("expected `#[builder]`"));
+}
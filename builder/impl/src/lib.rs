@@ -0,0 +1,39 @@
+// The expansion itself lives in derive_builder_core so that it can be used
+// outside of a procedural macro; these entry points only convert between
+// proc_macro and proc_macro2 tokens.
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+    derive_builder_core::derive(&derive_input).into()
+}
+
+/// Turns a function, or the methods of an `impl` block that are themselves marked `#[builder]`,
+/// into a builder taking the arguments as named setters:
+/// ```rust
+/// # use derive_builder::builder;
+/// # struct Conn;
+/// #[builder]
+/// fn connect(host: String, port: u16, timeout: Option<u64>) -> Conn {
+///     // ...
+/// #   Conn
+/// }
+///
+/// let conn = connect().host("localhost".to_owned()).port(8080).call();
+/// ```
+/// Arguments are classified exactly like the fields of `#[derive(Builder)]`, so `Option`
+/// arguments may be skipped and `#[builder(each = "...")]`, `#[builder(default)]` and
+/// `#[builder(field(...))]` can be placed on arguments.
+///
+/// `call()` moves the arguments out of the builder, so calling it again needs the required
+/// arguments to be set again. A required argument that was not set makes `call()` panic with a
+/// message naming it, like "`port` was not set".
+///
+/// Doc comments and `#[deprecated]` on the function move to the entry point, `#[must_use]`
+/// moves to `call()`, and `#[cfg]` applies to the builder as well.
+#[proc_macro_attribute]
+pub fn builder(args: TokenStream, input: TokenStream) -> TokenStream {
+    derive_builder_core::builder(args.into(), input.into()).into()
+}
@@ -0,0 +1,45 @@
+// Some fields are more convenient to build up in a different representation
+// than the one stored in the final struct. For example a name may be collected
+// as a String but stored as Arc<str>, or a set of tags may be accumulated one
+// at a time into a Vec and stored as a BTreeSet.
+//
+// Look for a field attribute #[builder(field(ty = "...", build = "..."))]. The
+// builder holds the field as the given type, the setters accept that type, and
+// build() evaluates the given expression with the built-up value bound to the
+// field's name to produce the value stored in the struct.
+
+use derive_builder::Builder;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(field(ty = "String", build = "Arc::from(executable)"))]
+    executable: Arc<str>,
+    #[builder(each = "tag", field(ty = "Vec<String>", build = "tags.into_iter().collect()"))]
+    tags: BTreeSet<String>,
+    #[builder(field(ty = "Option<u16>", build = "port.map(u32::from)"))]
+    port: Option<u32>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .tag("b".to_owned())
+        .tag("a".to_owned())
+        .tag("b".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(&*command.executable, "cargo");
+    assert_eq!(command.tags.into_iter().collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(command.port, None);
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .port(8080)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.port, Some(8080));
+}
@@ -0,0 +1,39 @@
+// Lookup tables are often built in `static` items, which can only be
+// initialized by constant expressions.
+//
+// Look for a struct attribute #[builder(const)]. With it, the builder uses the
+// owned pattern: builder(), every setter and build() are `const fn`, setters
+// take and return the builder by value, and build() returns the struct
+// directly, panicking if a required field was never set. Because the panic
+// happens during constant evaluation, a missing field in a static initializer
+// is reported at compile time.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Register {
+    name: &'static str,
+    width: u8,
+    reset: Option<u32>,
+    #[builder(field(ty = "u8", build = "offset as usize * 4"))]
+    offset: usize,
+}
+
+static REGISTERS: [Register; 2] = [
+    Register::builder()
+        .name("CTRL")
+        .width(32)
+        .reset(0x8000_0000)
+        .offset(0)
+        .build(),
+    Register::builder().name("DATA").width(8).offset(1).build(),
+];
+
+fn main() {
+    assert_eq!(REGISTERS[0].name, "CTRL");
+    assert_eq!(REGISTERS[0].reset, Some(0x8000_0000));
+    assert_eq!(REGISTERS[1].width, 8);
+    assert_eq!(REGISTERS[1].reset, None);
+    assert_eq!(REGISTERS[1].offset, 4);
+}
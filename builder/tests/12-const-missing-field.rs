@@ -0,0 +1,18 @@
+// A #[builder(const)] builder evaluated in a static initializer with a missing
+// required field should fail to compile, with the panic message naming the
+// field.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Register {
+    name: &'static str,
+    width: u8,
+}
+
+static CTRL: Register = Register::builder().name("CTRL").build();
+
+fn main() {
+    let _ = &CTRL;
+}
@@ -0,0 +1,179 @@
+// Functions with long argument lists benefit from named and optional arguments
+// just as much as structs with many fields do.
+//
+// Provide an attribute macro #[builder] that turns a function into an entry
+// point returning a builder, with one setter per argument and a call() method
+// which invokes the original function. Arguments are classified exactly like
+// struct fields: Option arguments may be left out, and #[builder(each = "...")]
+// and #[builder(default)] are recognized on arguments.
+//
+// call() moves the arguments out of the builder. Calling it while a required
+// argument is unset, including calling it a second time without setting the
+// arguments again, panics with a message naming the argument.
+//
+// The function's doc comments and #[deprecated] move to the entry point,
+// #[must_use] moves to call(), and #[cfg] applies to everything generated.
+//
+// For methods, place #[builder] on the impl block as well as on each method
+// that should get a builder. Methods taking &self or &mut self keep a
+// reference to the receiver inside the builder. `Self` in a method's signature
+// still refers to the type of the impl block, not to the builder.
+
+use derive_builder::builder;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+pub struct Conn {
+    addr: String,
+    timeout: Option<Duration>,
+    retries: u8,
+    tags: Vec<String>,
+}
+
+/// Opens a connection.
+#[builder]
+#[must_use]
+pub fn connect(
+    host: String,
+    port: u16,
+    timeout: Option<Duration>,
+    #[builder(default = "3")] retries: u8,
+    #[builder(each = "tag")] tags: Vec<String>,
+) -> Conn {
+    Conn {
+        addr: format!("{host}:{port}"),
+        timeout,
+        retries,
+        tags,
+    }
+}
+
+#[cfg(any())]
+#[builder]
+pub fn removed(value: TypeThatDoesNotExist) {}
+
+pub struct Client {
+    base: String,
+    sent: usize,
+}
+
+#[builder]
+impl Client {
+    #[builder]
+    pub fn new(base: String) -> Client {
+        Client { base, sent: 0 }
+    }
+
+    #[builder]
+    pub fn get(&self, path: String, #[builder(default)] query: String) -> String {
+        format!("{}{path}{query}", self.base)
+    }
+
+    #[builder]
+    pub fn send(&mut self, #[builder(each = "line")] body: Vec<String>) -> usize {
+        self.sent += body.len();
+        self.sent
+    }
+
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+
+    #[builder]
+    pub fn with_sent(base: String, sent: usize) -> Self {
+        Client { base, sent }
+    }
+
+    #[cfg(any())]
+    #[builder]
+    pub fn removed(&self, value: TypeThatDoesNotExist) {}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[builder]
+impl Point {
+    #[builder]
+    pub fn offset(&self, by: Self, times: Option<Vec<Self>>) -> Self {
+        let extra = times.unwrap_or_default().len() as i32;
+        Point {
+            x: self.x + by.x * (1 + extra),
+            y: self.y + by.y * (1 + extra),
+        }
+    }
+}
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_err();
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().unwrap().to_string(),
+    }
+}
+
+fn main() {
+    let conn = connect()
+        .host("localhost".to_owned())
+        .port(8080)
+        .tag("primary".to_owned())
+        .call();
+
+    assert_eq!(
+        conn,
+        Conn {
+            addr: "localhost:8080".to_owned(),
+            timeout: None,
+            retries: 3,
+            tags: vec!["primary".to_owned()],
+        }
+    );
+
+    let conn = connect()
+        .host("localhost".to_owned())
+        .port(8080)
+        .timeout(Duration::from_secs(1))
+        .retries(0)
+        .call();
+    assert_eq!(conn.timeout, Some(Duration::from_secs(1)));
+    assert_eq!(conn.retries, 0);
+
+    std::panic::set_hook(Box::new(|_| {}));
+    let message = panic_message(|| {
+        let _ = connect().host("localhost".to_owned()).call();
+    });
+    assert_eq!(message, "`port` was not set");
+
+    let mut builder = connect();
+    builder.host("localhost".to_owned()).port(8080);
+    let _ = builder.call();
+    let message = panic_message(|| {
+        let _ = builder.call();
+    });
+    assert_eq!(message, "`host` was not set");
+    let _ = std::panic::take_hook();
+
+    let mut client = Client::new().base("https://example.com".to_owned()).call();
+    let url = client.get().path("/index.html".to_owned()).call();
+    assert_eq!(url, "https://example.com/index.html");
+
+    client.send().line("a".to_owned()).line("b".to_owned()).call();
+    assert_eq!(client.sent(), 2);
+
+    let client = Client::with_sent()
+        .base("https://example.com".to_owned())
+        .sent(5)
+        .call();
+    assert_eq!(client.sent(), 5);
+
+    let origin = Point { x: 0, y: 0 };
+    let by = Point { x: 1, y: 2 };
+    assert_eq!(origin.offset().by(by).call(), Point { x: 1, y: 2 });
+    assert_eq!(
+        origin.offset().by(by).times(vec![by]).call(),
+        Point { x: 2, y: 4 }
+    );
+}
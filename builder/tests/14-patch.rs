@@ -0,0 +1,84 @@
+// PATCH-style APIs need a value with every field optional that can be applied
+// to an existing struct, overwriting only the fields that were provided.
+//
+// Look for a struct attribute #[builder(patch)]. It generates FooPatch holding
+// an Option for every field, with the same setters as the builder, an
+// apply(self, &mut Foo) method, and Foo::diff(&self, &Foo) which records every
+// field that differs between the two values.
+//
+// Diffing compares and clones the fields, so fields that are not PartialEq
+// and Clone can be left out of diff() with #[builder(skip_diff)], as are
+// fields with custom storage, whose builder value cannot be recovered. Both
+// can still be set on the patch and applied.
+//
+// Applying a patch replaces each provided field as a whole, so elements added
+// through an `each` setter on the patch replace those of the Vec rather than
+// being appended to them.
+
+use derive_builder::Builder;
+
+pub struct Handle(u32);
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(patch)]
+pub struct Config {
+    host: String,
+    port: u16,
+    #[builder(each = "plugin")]
+    plugins: Vec<String>,
+    motd: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(patch)]
+pub struct Session {
+    user: String,
+    #[builder(skip_diff)]
+    handle: Handle,
+}
+
+fn main() {
+    let mut config = Config::builder()
+        .host("localhost".to_owned())
+        .port(80)
+        .plugin("log".to_owned())
+        .build()
+        .unwrap();
+
+    let mut patch = ConfigPatch::default();
+    patch.port(8080).plugin("auth".to_owned()).motd("hi".to_owned());
+    patch.apply(&mut config);
+
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.plugins, ["auth"]);
+    assert_eq!(config.motd.as_deref(), Some("hi"));
+
+    let mut other = config.clone();
+    other.host = "example.com".to_owned();
+    let diff = config.diff(&other);
+    assert!(diff.port.is_none());
+    assert!(diff.plugins.is_none());
+
+    diff.apply(&mut config);
+    assert_eq!(config, other);
+
+    let mut session = Session {
+        user: "alice".to_owned(),
+        handle: Handle(1),
+    };
+    let other = Session {
+        user: "bob".to_owned(),
+        handle: Handle(2),
+    };
+    let diff = session.diff(&other);
+    assert!(diff.handle.is_none());
+    diff.apply(&mut session);
+    assert_eq!(session.user, "bob");
+    assert_eq!(session.handle.0, 1);
+
+    let mut patch = SessionPatch::default();
+    patch.handle(Handle(3));
+    patch.apply(&mut session);
+    assert_eq!(session.handle.0, 3);
+}
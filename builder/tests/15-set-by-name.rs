@@ -0,0 +1,97 @@
+// Service configs are often populated from environment variables or from
+// `--set key=value` command line flags, where both the field name and its
+// value arrive as strings.
+//
+// Look for a struct attribute #[builder(set_by_name)]. It generates
+// FooBuilder::set_by_name(&mut self, name, value), which parses the value with
+// FromStr for the named field (pushing one element for #[builder(each = ...)]
+// fields), and FooBuilder::from_env(prefix), which does the same for every
+// PREFIX_FIELD_NAME environment variable that is set. Variables for `each`
+// fields hold a comma-separated list of elements. Unknown names, parse
+// failures and variables that are not valid unicode are reported through
+// derive_builder::SetError, which names the field.
+//
+// Fields whose type is not FromStr are marked #[builder(skip_set_by_name)].
+// They are left out of both functions and can only be set with their setter.
+
+use derive_builder::{Builder, SetError};
+use std::net::IpAddr;
+
+#[derive(Debug, PartialEq)]
+pub struct Handler(u8);
+
+#[derive(Builder)]
+#[builder(set_by_name)]
+pub struct Service {
+    listen_addr: IpAddr,
+    port: u16,
+    #[builder(each = "peer")]
+    peers: Vec<String>,
+    name: Option<String>,
+    #[builder(skip_set_by_name)]
+    handler: Option<Handler>,
+}
+
+fn main() {
+    let mut builder = Service::builder();
+    builder.set_by_name("listen_addr", "127.0.0.1").unwrap();
+    builder.set_by_name("port", "8080").unwrap();
+    builder.set_by_name("peers", "a").unwrap();
+    builder.set_by_name("peers", "b").unwrap();
+    let service = builder.build().unwrap();
+
+    assert_eq!(service.listen_addr, IpAddr::from([127, 0, 0, 1]));
+    assert_eq!(service.port, 8080);
+    assert_eq!(service.peers, ["a", "b"]);
+    assert_eq!(service.name, None);
+
+    let mut builder = Service::builder();
+    assert_eq!(
+        builder.set_by_name("prot", "8080"),
+        Err(SetError::UnknownField("prot".to_owned())),
+    );
+    assert_eq!(
+        builder.set_by_name("handler", "1"),
+        Err(SetError::UnknownField("handler".to_owned())),
+    );
+    let err = builder.set_by_name("port", "http").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value `http` for field `port`: invalid digit found in string",
+    );
+
+    std::env::set_var("SVC_LISTEN_ADDR", "::1");
+    std::env::set_var("SVC_PORT", "443");
+    std::env::set_var("SVC_NAME", "edge");
+    std::env::set_var("SVC_HANDLER", "not a handler");
+    let service = ServiceBuilder::from_env("SVC").unwrap().build().unwrap();
+
+    assert_eq!(service.listen_addr, "::1".parse::<IpAddr>().unwrap());
+    assert_eq!(service.port, 443);
+    assert!(service.peers.is_empty());
+    assert_eq!(service.name.as_deref(), Some("edge"));
+    assert_eq!(service.handler, None);
+
+    std::env::set_var("SVC_PEERS", "x,y");
+    let service = ServiceBuilder::from_env("SVC").unwrap().build().unwrap();
+    assert_eq!(service.peers, ["x", "y"]);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+
+        std::env::set_var("BAD_PORT", std::ffi::OsString::from_vec(vec![0xff]));
+        let err = ServiceBuilder::from_env("BAD").err().unwrap();
+        assert_eq!(
+            err,
+            SetError::NotUnicode {
+                field: "port",
+                var: "BAD_PORT".to_owned(),
+            },
+        );
+        assert_eq!(
+            err.to_string(),
+            "environment variable `BAD_PORT` for field `port` is not valid unicode",
+        );
+    }
+}
@@ -0,0 +1,79 @@
+// Help text, config file templates and validation messages can all be
+// generated from the same knowledge of the fields that the builder has.
+//
+// Generate an associated constant Foo::BUILDER_FIELDS listing, for every
+// field, its name, its type as written, how the builder treats it and its doc
+// comment, using the derive_builder::FieldInfo type.
+
+use derive_builder::{Builder, FieldInfo, FieldKind};
+
+#[derive(Builder)]
+pub struct Command {
+    /// The program to run.
+    executable: String,
+    /// Arguments passed to the program,
+    /// in order.
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<std::path::PathBuf>,
+    #[builder(default = "10")]
+    niceness: u8,
+}
+
+#[derive(Builder)]
+pub struct Shapes {
+    key: [u8; 4],
+    hook: Box<dyn Fn(&str) -> u8 + Send>,
+    callback: fn(u8, &'static str) -> *const u8,
+    pairs: Vec<(u8, String)>,
+}
+
+fn main() {
+    assert_eq!(
+        Command::BUILDER_FIELDS,
+        [
+            FieldInfo {
+                name: "executable",
+                ty: "String",
+                kind: FieldKind::Required,
+                doc: "The program to run.",
+            },
+            FieldInfo {
+                name: "args",
+                ty: "Vec<String>",
+                kind: FieldKind::Repeated { each: "arg" },
+                doc: "Arguments passed to the program,\nin order.",
+            },
+            FieldInfo {
+                name: "current_dir",
+                ty: "Option<std::path::PathBuf>",
+                kind: FieldKind::Optional,
+                doc: "",
+            },
+            FieldInfo {
+                name: "niceness",
+                ty: "u8",
+                kind: FieldKind::Defaulted,
+                doc: "",
+            },
+        ],
+    );
+
+    let required: Vec<_> = Command::BUILDER_FIELDS
+        .iter()
+        .filter(|f| f.is_required())
+        .map(|f| f.name)
+        .collect();
+    assert_eq!(required, ["executable"]);
+
+    let types: Vec<_> = Shapes::BUILDER_FIELDS.iter().map(|f| f.ty).collect();
+    assert_eq!(
+        types,
+        [
+            "[u8; 4]",
+            "Box<dyn Fn(&str) -> u8 + Send>",
+            "fn(u8, &'static str) -> *const u8",
+            "Vec<(u8, String)>",
+        ],
+    );
+}
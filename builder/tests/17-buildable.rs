@@ -0,0 +1,85 @@
+// Generic code cannot name the builder of a type through the inherent
+// Foo::builder() function alone.
+//
+// Implement the derive_builder::Buildable trait for the struct and the
+// derive_builder::Builder trait for its generated builder, so that generic
+// factories and test fixtures can start from T::builder() and finish with
+// build(). A required field that was never set makes build() return
+// Err(BuildError) rather than panic, for const builders too.
+
+use derive_builder::{BuildError, Buildable, Builder};
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    executable: Option<String>,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const)]
+pub struct Limits {
+    #[builder(default = "16")]
+    depth: u8,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Process {
+    executable: String,
+    pid: u32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const)]
+pub struct Port {
+    number: u16,
+}
+
+fn fixture<T: Buildable>() -> Result<T, BuildError>
+where
+    T::Builder: Builder<Error = BuildError>,
+{
+    T::builder().build()
+}
+
+fn main() {
+    let command: Command = fixture().unwrap();
+    assert_eq!(
+        command,
+        Command {
+            executable: None,
+            args: vec![],
+        }
+    );
+
+    let limits: Limits = fixture().unwrap();
+    assert_eq!(limits, Limits { depth: 16 });
+
+    let mut builder = <Command as Buildable>::builder();
+    builder.arg("--release".to_owned());
+    let command = Builder::build(&mut builder).unwrap();
+    assert_eq!(command.args, ["--release"]);
+    let command = Builder::build(&mut builder).unwrap();
+    assert!(command.args.is_empty());
+
+    assert_eq!(fixture::<Process>(), Err(BuildError));
+    assert_eq!(fixture::<Port>(), Err(BuildError));
+
+    // a failed build leaves the fields that were set in place
+    let mut builder = <Process as Buildable>::builder();
+    builder.executable("cargo".to_owned());
+    assert_eq!(Builder::build(&mut builder), Err(BuildError));
+    builder.pid(7);
+    assert_eq!(
+        Builder::build(&mut builder),
+        Ok(Process {
+            executable: "cargo".to_owned(),
+            pid: 7,
+        })
+    );
+
+    let mut builder = <Port as Buildable>::builder();
+    assert_eq!(Builder::build(&mut builder), Err(BuildError));
+    builder = builder.number(80);
+    assert_eq!(Builder::build(&mut builder), Ok(Port { number: 80 }));
+}
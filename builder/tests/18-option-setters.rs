@@ -0,0 +1,57 @@
+// The setter of an Option field takes the inner type, which leaves no way to
+// forward an Option the caller already has, to explicitly set None, or to
+// forget a value that was set earlier.
+//
+// For every Option field, also generate maybe_field(Option<T>), which stores
+// the given Option as is, and unset_field(), which returns the field to its
+// initial never-set state. The attribute
+// #[builder(setter(strip_option = false))] makes the main setter take the
+// Option directly instead, in which case maybe_field is not generated.
+//
+// The difference between an explicit None and an unset field is visible in a
+// #[builder(patch)]: the former clears the field when applied, the latter
+// leaves it untouched.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(patch)]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+    #[builder(setter(strip_option = false))]
+    timeout: Option<u64>,
+}
+
+fn main() {
+    let inherited: Option<String> = None;
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .maybe_current_dir(inherited)
+        .timeout(Some(30))
+        .build()
+        .unwrap();
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.timeout, Some(30));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .timeout(Some(30))
+        .unset_timeout()
+        .build()
+        .unwrap();
+    assert_eq!(command.timeout, None);
+
+    let mut command = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .timeout(Some(30))
+        .build()
+        .unwrap();
+    let mut patch = CommandPatch::default();
+    patch.maybe_current_dir(None).timeout(Some(5)).unset_timeout();
+    patch.apply(&mut command);
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.timeout, Some(30));
+}
@@ -0,0 +1,11 @@
+// The strip_option setting only makes sense on Option fields.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(strip_option = false))]
+    executable: String,
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+// Crates that wrap derive_builder in a facade leave their users without a
+// direct dependency on derive_builder, so generated code must not hardcode
+// paths like ::derive_builder::Buildable.
+//
+// Look for a struct attribute #[builder(crate = "...")] giving the path at
+// which the facade re-exports derive_builder, and route every generated path
+// through it. To check that nothing refers to ::derive_builder anymore, this
+// test makes that name point at an unrelated crate.
+
+extern crate core as derive_builder;
+
+use derive_builder_facade::builder::{Buildable, Builder, FieldKind};
+
+#[derive(derive_builder_facade::builder::Builder)]
+#[builder(crate = "::derive_builder_facade::builder")]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let mut builder = <Command as Buildable>::builder();
+    builder.executable("cargo".to_owned());
+    let command = Builder::build(&mut builder).unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir, None);
+
+    assert_eq!(Command::BUILDER_FIELDS[1].kind, FieldKind::Optional);
+
+    let _ = derive_builder::mem::size_of::<Command>();
+}
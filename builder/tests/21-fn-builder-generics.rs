@@ -0,0 +1,76 @@
+// Functions taking references or generic parameters need their builders to be
+// generic too, since the builder holds the arguments until call().
+//
+// Carry the function's lifetimes, type parameters and where clause over to the
+// generated builder. Lifetimes elided in argument types, like the one in
+// `name: &str`, are given names of their own, and type parameters used only by
+// the return type are passed explicitly to the original function.
+
+use derive_builder::builder;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[builder]
+pub fn greet(name: &str, greeting: Option<&str>) -> String {
+    format!("{}, {name}!", greeting.unwrap_or("Hello"))
+}
+
+#[builder]
+pub fn longest<'a>(first: &'a str, second: &'a str) -> &'a str {
+    if second.len() > first.len() {
+        second
+    } else {
+        first
+    }
+}
+
+#[builder]
+pub fn label<T: Display, const N: usize>(value: T, #[builder(default)] width: usize) -> String {
+    format!("{:>width$}{}", value, "!".repeat(N))
+}
+
+#[builder]
+pub fn parse<T>(input: &str) -> Option<T>
+where
+    T: FromStr,
+{
+    input.trim().parse().ok()
+}
+
+pub struct Registry {
+    entries: HashMap<String, u32>,
+}
+
+#[builder]
+impl Registry {
+    #[builder]
+    pub fn get(&self, key: &str, #[builder(default)] fallback: u32) -> u32 {
+        self.entries.get(key).copied().unwrap_or(fallback)
+    }
+
+    #[builder]
+    pub fn insert<V: Into<u32>>(&mut self, key: &str, value: V) {
+        self.entries.insert(key.to_owned(), value.into());
+    }
+}
+
+fn main() {
+    let name = String::from("world");
+    assert_eq!(greet().name(&name).call(), "Hello, world!");
+    assert_eq!(greet().name("you").greeting("Hi").call(), "Hi, you!");
+
+    assert_eq!(longest().first("ab").second("abc").call(), "abc");
+
+    assert_eq!(label::<_, 2>().value(7).width(3).call(), "  7!!");
+
+    assert_eq!(parse::<u8>().input(" 42 ").call(), Some(42));
+    assert_eq!(parse::<u8>().input("x").call(), None);
+
+    let mut registry = Registry {
+        entries: HashMap::new(),
+    };
+    registry.insert().key("a").value(3u8).call();
+    assert_eq!(registry.get().key("a").call(), 3);
+    assert_eq!(registry.get().key("b").fallback(9).call(), 9);
+}
@@ -0,0 +1,15 @@
+// Default::default() cannot be called in a const fn, so a #[builder(default)]
+// without a value should be reported on the attribute when the builder is
+// #[builder(const)], rather than as an error inside the generated build().
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Register {
+    name: &'static str,
+    #[builder(default)]
+    width: u8,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+// Attributes describing a function marked #[builder] belong on the generated
+// items its callers use: #[deprecated] on the entry point, whose use should
+// warn, and #[must_use] on call(), whose result is the function's result.
+
+#![deny(deprecated, unused_must_use)]
+
+use derive_builder::builder;
+
+#[builder]
+#[deprecated = "use `connect` instead"]
+pub fn open(host: String) -> String {
+    host
+}
+
+#[builder]
+#[must_use]
+pub fn connect(host: String) -> String {
+    host
+}
+
+fn main() {
+    let _ = open().host("localhost".to_owned()).call();
+    connect().host("localhost".to_owned()).call();
+}
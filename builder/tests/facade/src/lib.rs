@@ -0,0 +1,5 @@
+// Stands in for a facade crate that wraps derive_builder for its users, who
+// then only depend on the facade. Used by tests/20-crate-path.rs.
+pub mod builder {
+    pub use derive_builder::*;
+}
@@ -1,8 +1,6 @@
 use derive_builder as _;
-use indoc as _;
-use proc_macro2 as _;
-use quote as _;
-use syn as _;
+use derive_builder_facade as _;
+use derive_builder_impl as _;
 
 #[test]
 fn tests() {
@@ -16,4 +14,18 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-custom-storage.rs");
+    t.pass("tests/11-const-builder.rs");
+    t.compile_fail("tests/12-const-missing-field.rs");
+    t.pass("tests/13-fn-builder.rs");
+    t.pass("tests/14-patch.rs");
+    t.pass("tests/15-set-by-name.rs");
+    t.pass("tests/16-field-info.rs");
+    t.pass("tests/17-buildable.rs");
+    t.pass("tests/18-option-setters.rs");
+    t.compile_fail("tests/19-strip-option-wrong.rs");
+    t.pass("tests/20-crate-path.rs");
+    t.pass("tests/21-fn-builder-generics.rs");
+    t.compile_fail("tests/22-const-bare-default.rs");
+    t.compile_fail("tests/23-fn-builder-attrs.rs");
 }
@@ -0,0 +1,985 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote, ToTokens};
+
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::Attribute;
+use syn::Expr;
+use syn::ExprPath;
+use syn::Field;
+use syn::Fields;
+use syn::Generics;
+use syn::LitInt;
+use syn::LitStr;
+use syn::Member;
+use syn::Meta;
+use syn::Type;
+use syn::TypePath;
+use syn::{parse_macro_input, parse_quote, DeriveInput, Ident, Token, WherePredicate};
+
+struct DebugField {
+    /// The field name, or its index for tuple fields
+    member: Member,
+    /// The variable the field is bound to when matching on `self`
+    binding: Ident,
+    /// The field type, like `u8` or `PhantomData<T>`, used to infer trait bounds
+    ty: Type,
+    /// How the field's value is shown, declared via `#[debug = "..."]` or `#[debug(...)]`
+    format: FieldFormat,
+    /// The name shown in place of the field's own, declared via `#[debug(rename = "...")]`
+    rename: Option<LitStr>,
+    /// A predicate taking a reference to the field which leaves the field out of the output
+    /// when it returns true, declared via `#[debug(skip_if = "Option::is_none")]`
+    skip_if: Option<ExprPath>,
+    /// Handwritten bounds replacing those inferred from this field's type, declared via
+    /// `#[debug(bound = "T::Value: Debug")]`
+    bound: Option<Punctuated<WherePredicate, Token![,]>>,
+}
+
+/// The ways a field's value can be shown in the output.
+enum FieldFormat {
+    /// The field's own `Debug` impl
+    Debug,
+    /// A format string used in place of the field's own `Debug` impl, declared via:
+    /// ```rust
+    /// # use derive_debug::CustomDebug;
+    /// # #[derive(CustomDebug)]
+    /// # struct Foo {
+    ///     #[debug = "0b{:08b}"]
+    ///     bitmask: u8,
+    /// # }
+    /// ```
+    Format(LitStr),
+    /// The field is left out, declared via `#[debug(skip)]`
+    Skip,
+    /// The value is replaced by `"***"`, declared via `#[debug(redact)]`
+    Redact,
+    /// The value is replaced by whatever the function returns when given a reference to the
+    /// field, declared via `#[debug(redact_with = "last_four")]`
+    RedactWith(ExprPath),
+    /// The value is written by a function taking the field and the formatter, like a `Debug`
+    /// impl would, declared via `#[debug(with = "fmt_time")]`
+    With(ExprPath),
+    /// Only the first items of a collection are shown, followed by how many were left out,
+    /// declared via `#[debug(max_items = 4)]`
+    MaxItems(usize),
+    /// Only the length of a collection is shown, declared via `#[debug(len_only)]`
+    LenOnly,
+    /// Bytes are shown as a compact hex string, declared via `#[debug(hex)]`
+    Hex,
+}
+
+impl FieldFormat {
+    /// Whether the field's type must implement `Debug` for the value to be shown.
+    fn needs_debug(&self) -> bool {
+        matches!(self, Self::Debug | Self::Format(_) | Self::MaxItems(_))
+    }
+}
+
+impl DebugField {
+    fn new(index: usize, field: &Field) -> syn::Result<Self> {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        let binding = format_ident!("__field_{}", index);
+        let mut format = FieldFormat::Debug;
+        let mut rename = None;
+        let mut skip_if = None;
+        let mut bound = None;
+        for a in &field.attrs {
+            if !a.path().is_ident("debug") {
+                continue;
+            }
+            match &a.meta {
+                Meta::NameValue(mnv) => match &mnv.value {
+                    Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lstr),
+                        ..
+                    }) => set_format(&mut format, FieldFormat::Format(lstr.clone()), a)?,
+                    value => {
+                        return Err(syn::Error::new_spanned(value, "expected a format string"))
+                    }
+                },
+                Meta::List(_) => a.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        if field.ident.is_none() {
+                            return Err(meta.error("only named fields can be renamed"));
+                        }
+                        rename = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    } else if meta.path.is_ident("skip_if") {
+                        let lstr: LitStr = meta.value()?.parse()?;
+                        skip_if = Some(lstr.parse()?);
+                        return Ok(());
+                    } else if meta.path.is_ident("bound") {
+                        let lstr: LitStr = meta.value()?.parse()?;
+                        bound = Some(lstr.parse_with(Punctuated::parse_terminated)?);
+                        return Ok(());
+                    }
+
+                    let new_format = if meta.path.is_ident("skip") {
+                        FieldFormat::Skip
+                    } else if meta.path.is_ident("redact") {
+                        FieldFormat::Redact
+                    } else if meta.path.is_ident("redact_with") {
+                        let lstr: LitStr = meta.value()?.parse()?;
+                        FieldFormat::RedactWith(lstr.parse()?)
+                    } else if meta.path.is_ident("with") {
+                        let lstr: LitStr = meta.value()?.parse()?;
+                        FieldFormat::With(lstr.parse()?)
+                    } else if meta.path.is_ident("max_items") {
+                        let lint: LitInt = meta.value()?.parse()?;
+                        FieldFormat::MaxItems(lint.base10_parse()?)
+                    } else if meta.path.is_ident("len_only") {
+                        FieldFormat::LenOnly
+                    } else if meta.path.is_ident("hex") {
+                        FieldFormat::Hex
+                    } else {
+                        return Err(meta.error(
+                            r#"expected one of `skip`, `redact`, `redact_with = "..."`, `with = "..."`, `max_items = N`, `len_only`, `hex`, `rename = "..."`, `skip_if = "..."` or `bound = "..."`"#,
+                        ));
+                    };
+                    set_format(&mut format, new_format, a)
+                })?,
+                meta => {
+                    return Err(syn::Error::new_spanned(meta, r#"expected `debug = "..."`"#));
+                }
+            }
+        }
+
+        Ok(Self {
+            member,
+            binding,
+            ty: field.ty.clone(),
+            format,
+            rename,
+            skip_if,
+            bound,
+        })
+    }
+
+    /// The pattern binding this field when matching on `self`, `member: binding`, or
+    /// `member: _` for skipped fields that are never read.
+    fn get_pattern(&self) -> proc_macro2::TokenStream {
+        let member = &self.member;
+        let binding = &self.binding;
+        match self.format {
+            FieldFormat::Skip => quote!(#member: _),
+            _ => quote!(#member: #binding),
+        }
+    }
+
+    /// The value shown for this field, a reference to something implementing `Debug`, or `None`
+    /// for skipped fields. This is the field itself unless its `#[debug(...)]` attributes ask
+    /// for something else, like `format_args!` applied to a `#[debug = "..."]` format string.
+    fn get_value(&self) -> Option<proc_macro2::TokenStream> {
+        let binding = &self.binding;
+
+        Some(match &self.format {
+            FieldFormat::Debug => quote!(#binding),
+            FieldFormat::Format(format) => quote!(&::core::format_args!(#format, #binding)),
+            FieldFormat::Skip => return None,
+            FieldFormat::Redact => quote!(&"***"),
+            FieldFormat::RedactWith(path) => quote!(&#path(#binding)),
+            FieldFormat::With(path) => quote!(&__DebugWith(#binding, #path)),
+            FieldFormat::MaxItems(max) => quote!(&__DebugTruncated(#binding, #max)),
+            FieldFormat::LenOnly => quote!(&#binding.len()),
+            FieldFormat::Hex => {
+                quote!(&__DebugHex(::core::convert::AsRef::<[u8]>::as_ref(#binding)))
+            }
+        })
+    }
+
+    /// This function creates the statement adding this field to the `DebugStruct` helper, or
+    /// the `DebugTuple` helper for tuple fields. For example, if we have
+    /// ```rust
+    /// struct Foo {
+    ///     alpha: String,
+    ///     bitmask: u8,
+    /// }
+    /// ```
+    /// then this function will generate one of the statements, like
+    /// ```rust
+    /// # struct Foo { alpha: String }
+    /// # impl std::fmt::Debug for Foo {
+    /// # fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// # let Foo { alpha: __field_0 } = self;
+    /// # let mut __debug = f.debug_struct("Foo");
+    ///     __debug.field("alpha", __field_0);
+    /// # __debug.finish()
+    /// # }
+    /// # }
+    /// ```
+    /// Skipped fields generate no statement at all, and fields with `#[debug(skip_if = "...")]`
+    /// are only added when the predicate returns false.
+    fn get_field_call(&self) -> proc_macro2::TokenStream {
+        let Some(value) = self.get_value() else {
+            return quote!();
+        };
+
+        let call = match self.get_name() {
+            Some(key) => quote!(
+                __debug.field(#key, #value);
+            ),
+            None => quote!(
+                __debug.field(#value);
+            ),
+        };
+        self.guard_skip_if(call)
+    }
+
+    /// This function creates the statement passing this field to the visitor of a
+    /// `#[debug(visit)]` type, under the same name and with the same value as in the `Debug`
+    /// output, or the index for tuple fields:
+    /// ```rust
+    /// # struct Foo { alpha: String }
+    /// # impl Foo {
+    /// # fn visit_fields(&self, visitor: &mut dyn derive_debug::FieldVisitor) {
+    /// # let Foo { alpha: __field_0 } = self;
+    ///     visitor.visit_field("alpha", __field_0);
+    /// # }
+    /// # }
+    /// ```
+    fn get_visit_call(&self) -> proc_macro2::TokenStream {
+        let Some(value) = self.get_value() else {
+            return quote!();
+        };
+
+        let key = match (self.get_name(), &self.member) {
+            (Some(key), _) => key,
+            (None, Member::Unnamed(index)) => index.index.to_string(),
+            (None, Member::Named(_)) => unreachable!("named fields always have a name"),
+        };
+        self.guard_skip_if(quote!(
+            visitor.visit_field(#key, #value);
+        ))
+    }
+
+    /// The name this field is shown under, or `None` for tuple fields.
+    fn get_name(&self) -> Option<String> {
+        match (&self.rename, &self.member) {
+            (Some(rename), _) => Some(rename.value()),
+            (None, Member::Named(name)) => Some(name.to_string()),
+            (None, Member::Unnamed(_)) => None,
+        }
+    }
+
+    /// Wraps a statement showing this field so that it only runs when the field's
+    /// `#[debug(skip_if = "...")]` predicate, if any, returns false.
+    fn guard_skip_if(&self, statement: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match &self.skip_if {
+            Some(predicate) => {
+                let binding = &self.binding;
+                quote!(
+                    if !#predicate(#binding) {
+                        #statement
+                    }
+                )
+            }
+            None => statement,
+        }
+    }
+}
+
+/// Records `new` as the format of a field, unless another attribute already chose one.
+fn set_format(format: &mut FieldFormat, new: FieldFormat, attr: &Attribute) -> syn::Result<()> {
+    if !matches!(format, FieldFormat::Debug) {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "conflicting `debug` attributes on this field",
+        ));
+    }
+    *format = new;
+    Ok(())
+}
+
+/// A struct, or one variant of an enum, along with its fields.
+struct DebugVariant {
+    /// The path matching this variant in patterns, `Self` or `Self::Variant`
+    path: proc_macro2::TokenStream,
+    /// The name shown in the output
+    name: String,
+    /// Whether the fields are named, unnamed or absent
+    style: Fields,
+    fields: Vec<DebugField>,
+}
+
+impl DebugVariant {
+    fn new(path: proc_macro2::TokenStream, ident: &Ident, fields: &Fields) -> syn::Result<Self> {
+        let debug_fields = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| DebugField::new(i, field))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            path,
+            name: ident.to_string(),
+            style: fields.clone(),
+            fields: debug_fields,
+        })
+    }
+
+    /// This function creates the match arm formatting this variant, like
+    /// ```rust
+    /// # enum Foo { Alpha(u8), Beta }
+    /// # impl std::fmt::Debug for Foo {
+    /// # fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// # match self {
+    ///     Self::Alpha { 0: __field_0 } => {
+    ///         let mut __debug = f.debug_tuple("Alpha");
+    ///         __debug.field(__field_0);
+    ///         __debug.finish()
+    ///     }
+    /// # Self::Beta => f.write_str("Beta"),
+    /// # }
+    /// # }
+    /// # }
+    /// ```
+    /// using `debug_struct` for named fields and printing just the name for unit variants. When
+    /// a field is skipped, the output ends in `..` via `finish_non_exhaustive`.
+    fn get_match_arm(&self) -> proc_macro2::TokenStream {
+        let path = &self.path;
+        let name = &self.name;
+        let patterns = self.fields.iter().map(DebugField::get_pattern);
+        let calls = self.fields.iter().map(DebugField::get_field_call);
+        // let readers know that the output does not show every field
+        let finish = if self
+            .fields
+            .iter()
+            .any(|f| matches!(f.format, FieldFormat::Skip))
+        {
+            quote!(finish_non_exhaustive)
+        } else {
+            quote!(finish)
+        };
+
+        let body = match &self.style {
+            Fields::Named(_) => quote!({
+                let mut __debug = f.debug_struct(#name);
+                #(#calls)*
+                __debug.#finish()
+            }),
+            Fields::Unnamed(_) => quote!({
+                let mut __debug = f.debug_tuple(#name);
+                #(#calls)*
+                __debug.#finish()
+            }),
+            Fields::Unit => quote!(
+                f.write_str(#name)
+            ),
+        };
+
+        quote!(
+            #path { #(#patterns),* } => #body,
+        )
+    }
+
+    /// This function creates the match arm of `visit_fields` for this variant, which passes each
+    /// field shown by the `Debug` output to the visitor.
+    fn get_visit_arm(&self) -> proc_macro2::TokenStream {
+        let path = &self.path;
+        let patterns = self.fields.iter().map(DebugField::get_pattern);
+        let calls = self.fields.iter().map(DebugField::get_visit_call);
+
+        quote!(
+            #path { #(#patterns),* } => {
+                #(#calls)*
+            }
+        )
+    }
+}
+
+/// Everything that can be declared on the struct itself via `#[debug(...)]`.
+#[derive(Default)]
+struct StructAttrs {
+    /// Handwritten bounds replacing all inferred ones, declared via
+    /// `#[debug(bound = "T::Value: Debug")]`.
+    bound: Option<Punctuated<WherePredicate, Token![,]>>,
+    /// The name shown in place of the struct's own, declared via `#[debug(name = "...")]`.
+    name: Option<LitStr>,
+    /// Whether a newtype shows just its field, declared via `#[debug(transparent)]`.
+    transparent: bool,
+    /// Whether to generate `visit_fields`, declared via `#[debug(visit)]`.
+    visit: bool,
+}
+
+fn get_struct_attrs(input: &DeriveInput) -> syn::Result<StructAttrs> {
+    let mut attrs = StructAttrs::default();
+    for a in &input.attrs {
+        if !a.path().is_ident("debug") {
+            continue;
+        }
+        a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("bound") {
+                let lstr: LitStr = meta.value()?.parse()?;
+                attrs.bound = Some(lstr.parse_with(Punctuated::parse_terminated)?);
+                Ok(())
+            } else if meta.path.is_ident("name") {
+                attrs.name = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("transparent") {
+                attrs.transparent = true;
+                Ok(())
+            } else if meta.path.is_ident("visit") {
+                attrs.visit = true;
+                Ok(())
+            } else {
+                Err(meta.error(
+                    r#"expected `debug(bound = "...")`, `debug(name = "...")`, `debug(transparent)` or `debug(visit)`"#,
+                ))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// Finds the name shown in place of an enum variant's own, declared on the variant via
+/// `#[debug(name = "...")]`.
+fn get_variant_name(attrs: &[Attribute]) -> syn::Result<Option<LitStr>> {
+    let mut name = None;
+    for a in attrs {
+        if !a.path().is_ident("debug") {
+            continue;
+        }
+        a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error(r#"expected `debug(name = "...")`"#))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+/// Walks field types looking for the places a formatting bound like `Debug` is needed: type
+/// parameters used directly, and associated types of type parameters like `T::Value` or
+/// `<T as Trait>::Value`, wherever they are nested. Anything inside a `PhantomData` or a function
+/// pointer is skipped, since `PhantomData<T>` and `fn(T)` are `Debug` whatever `T` is.
+struct BoundCollector<'a> {
+    params: Vec<&'a Ident>,
+    used: Vec<&'a Ident>,
+    projections: Vec<TypePath>,
+}
+
+impl<'ast> Visit<'ast> for BoundCollector<'_> {
+    fn visit_type_path(&mut self, type_path: &'ast TypePath) {
+        let segments = &type_path.path.segments;
+        if segments.last().is_some_and(|s| s.ident == "PhantomData") {
+            return;
+        }
+        if let Some(qself) = &type_path.qself {
+            // a projection like `<T as Trait>::Value` needs a bound of its own, not one on `T`
+            let mut inner = BoundCollector {
+                params: self.params.clone(),
+                used: vec![],
+                projections: vec![],
+            };
+            inner.visit_type(&qself.ty);
+            if !inner.used.is_empty() || !inner.projections.is_empty() {
+                self.projections.push(type_path.clone());
+                return;
+            }
+        } else {
+            let first = &segments.first().expect("path has a segment").ident;
+            if let Some(param) = self.params.iter().find(|p| **p == first) {
+                if segments.len() == 1 {
+                    if !self.used.contains(param) {
+                        self.used.push(param);
+                    }
+                } else {
+                    self.projections.push(type_path.clone());
+                }
+                return;
+            }
+        }
+        visit::visit_type_path(self, type_path);
+    }
+
+    fn visit_type_bare_fn(&mut self, _: &'ast syn::TypeBareFn) {}
+}
+
+/// Infers the bounds needed for values of the given types to be formatted with `trait_path`.
+fn infer_bounds<'a>(
+    generics: &Generics,
+    types: impl IntoIterator<Item = &'a Type>,
+    trait_path: &proc_macro2::TokenStream,
+) -> Vec<WherePredicate> {
+    let mut collector = BoundCollector {
+        params: generics.type_params().map(|p| &p.ident).collect(),
+        used: vec![],
+        projections: vec![],
+    };
+    for ty in types {
+        collector.visit_type(ty);
+    }
+    let params = collector.used.iter();
+    let projections = collector.projections.iter();
+    params
+        .map(|param| parse_quote!(#param: #trait_path))
+        .chain(projections.map(|path| parse_quote!(#path: #trait_path)))
+        .collect()
+}
+
+/// Removes repeated predicates, which appear when several fields need the same bound.
+fn dedup_predicates(predicates: Vec<WherePredicate>) -> Vec<WherePredicate> {
+    let mut seen = vec![];
+    predicates
+        .into_iter()
+        .filter(|p| {
+            let key = p.to_token_stream().to_string();
+            let new = !seen.contains(&key);
+            seen.push(key);
+            new
+        })
+        .collect()
+}
+
+/// Adds the `Debug` bounds the generated impl needs to `generics`, either those given in
+/// `#[debug(bound = "...")]` on the struct, or those given on each field or inferred from the
+/// field's type.
+fn add_trait_bounds(generics: &mut Generics, variants: &[DebugVariant], attrs: &StructAttrs) {
+    let predicates: Vec<WherePredicate> = if let Some(bound) = &attrs.bound {
+        bound.iter().cloned().collect()
+    } else {
+        let mut predicates = vec![];
+        for field in variants.iter().flat_map(|v| &v.fields) {
+            match &field.bound {
+                Some(bound) => predicates.extend(bound.iter().cloned()),
+                None if field.format.needs_debug() => predicates.extend(infer_bounds(
+                    generics,
+                    [&field.ty],
+                    &quote!(::core::fmt::Debug),
+                )),
+                None => {}
+            }
+        }
+        predicates
+    };
+
+    generics
+        .make_where_clause()
+        .predicates
+        .extend(dedup_predicates(predicates));
+}
+
+/// Creates the helper types that some field formats pass to `debug_struct`, which only accepts
+/// `Debug` values. They are declared inside the generated `fmt` so that they cannot clash with
+/// anything in the user's crate, and only when a field needs them.
+fn create_helpers(variants: &[DebugVariant]) -> proc_macro2::TokenStream {
+    let formats: Vec<&FieldFormat> = variants
+        .iter()
+        .flat_map(|v| &v.fields)
+        .map(|f| &f.format)
+        .collect();
+    let mut helpers = quote!();
+
+    if formats.iter().any(|f| matches!(f, FieldFormat::With(_))) {
+        helpers.extend(quote!(
+            struct __DebugWith<'a, T: ?Sized>(
+                &'a T,
+                fn(&T, &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result,
+            );
+
+            impl<T: ?Sized> ::core::fmt::Debug for __DebugWith<'_, T> {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    (self.1)(self.0, f)
+                }
+            }
+        ));
+    }
+
+    if formats
+        .iter()
+        .any(|f| matches!(f, FieldFormat::MaxItems(_)))
+    {
+        helpers.extend(quote!(
+            struct __DebugTruncated<'a, C: ?Sized>(&'a C, usize);
+
+            impl<'a, C: ?Sized> ::core::fmt::Debug for __DebugTruncated<'a, C>
+            where
+                &'a C: ::core::iter::IntoIterator,
+                <&'a C as ::core::iter::IntoIterator>::Item: ::core::fmt::Debug,
+            {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    let mut list = f.debug_list();
+                    let mut more = 0usize;
+                    let items = ::core::iter::IntoIterator::into_iter(self.0);
+                    for (i, item) in ::core::iter::Iterator::enumerate(items) {
+                        if i < self.1 {
+                            list.entry(&item);
+                        } else {
+                            more += 1;
+                        }
+                    }
+                    if more > 0 {
+                        list.entry(&::core::format_args!(".. ({} more)", more));
+                    }
+                    list.finish()
+                }
+            }
+        ));
+    }
+
+    if formats.iter().any(|f| matches!(f, FieldFormat::Hex)) {
+        helpers.extend(quote!(
+            struct __DebugHex<'a>(&'a [u8]);
+
+            impl ::core::fmt::Debug for __DebugHex<'_> {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    for byte in self.0 {
+                        ::core::write!(f, "{:02x}", byte)?;
+                    }
+                    ::core::result::Result::Ok(())
+                }
+            }
+        ));
+    }
+
+    helpers
+}
+
+/// Collects the struct, or each variant of the enum, along with the attributes placed on it and
+/// its name.
+fn get_variants<'a>(
+    derive_input: &'a DeriveInput,
+    derive_name: &str,
+) -> syn::Result<Vec<(DebugVariant, &'a [Attribute], &'a Ident)>> {
+    match &derive_input.data {
+        syn::Data::Struct(data) => {
+            let ident = &derive_input.ident;
+            let variant = DebugVariant::new(quote!(Self), ident, &data.fields)?;
+            Ok(vec![(variant, &derive_input.attrs, ident)])
+        }
+        syn::Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|v| {
+                let ident = &v.ident;
+                let variant = DebugVariant::new(quote!(Self::#ident), ident, &v.fields)?;
+                Ok((variant, &v.attrs[..], ident))
+            })
+            .collect(),
+        syn::Data::Union(_) => Err(syn::Error::new_spanned(
+            derive_input,
+            format!("{} does not support unions", derive_name),
+        )),
+    }
+}
+
+/// Creates the only match arm of a `#[debug(transparent)]` newtype, which hands the formatter
+/// straight to the value of its field.
+fn get_transparent_arm(
+    derive_input: &DeriveInput,
+    variant: &DebugVariant,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let value = match &variant.fields[..] {
+        [field] => field.get_value(),
+        _ => None,
+    };
+    let Some(value) = value else {
+        return Err(syn::Error::new_spanned(
+            derive_input,
+            "`debug(transparent)` requires exactly one field which is not skipped",
+        ));
+    };
+    let path = &variant.path;
+    let pattern = variant.fields[0].get_pattern();
+
+    Ok(quote!(
+        #path { #pattern } => ::core::fmt::Debug::fmt(#value, f),
+    ))
+}
+
+fn expand(derive_input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_attrs = get_struct_attrs(derive_input)?;
+    let struct_name = &derive_input.ident;
+
+    let is_enum = matches!(derive_input.data, syn::Data::Enum(_));
+    if is_enum && (struct_attrs.name.is_some() || struct_attrs.transparent) {
+        return Err(syn::Error::new_spanned(
+            derive_input,
+            "`debug(name)` and `debug(transparent)` are not supported on enums, \
+             place `debug(name)` on the variants instead",
+        ));
+    }
+
+    let mut variants = vec![];
+    for (mut variant, attrs, _) in get_variants(derive_input, "CustomDebug")? {
+        let name = if is_enum {
+            get_variant_name(attrs)?
+        } else {
+            struct_attrs.name.clone()
+        };
+        if let Some(name) = name {
+            variant.name = name.value();
+        }
+        variants.push(variant);
+    }
+
+    let mut generics = derive_input.generics.clone();
+    add_trait_bounds(&mut generics, &variants, &struct_attrs);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // empty enums have no value to format, and matching on the reference would not be exhaustive
+    let scrutinee = if variants.is_empty() {
+        quote!(*self)
+    } else {
+        quote!(self)
+    };
+    let arms = if struct_attrs.transparent {
+        vec![get_transparent_arm(derive_input, &variants[0])?]
+    } else {
+        variants.iter().map(DebugVariant::get_match_arm).collect()
+    };
+
+    let helpers = create_helpers(&variants);
+
+    let visit_fields = if struct_attrs.visit {
+        let arms = variants.iter().map(DebugVariant::get_visit_arm);
+        quote!(
+            #[automatically_derived]
+            impl #impl_generics #struct_name #ty_generics #where_clause {
+                /// Calls the visitor with the name and value of each field shown by this
+                /// type's `Debug` output.
+                pub fn visit_fields(&self, visitor: &mut dyn ::derive_debug::FieldVisitor) {
+                    #helpers
+
+                    match #scrutinee {
+                        #(#arms)*
+                    }
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    Ok(quote!(
+        #[automatically_derived]
+        impl #impl_generics ::core::fmt::Debug for #struct_name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #helpers
+
+                match #scrutinee {
+                    #(#arms)*
+                }
+            }
+        }
+
+        #visit_fields
+    ))
+}
+
+#[proc_macro_derive(CustomDebug, attributes(debug))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+
+    match expand(&derive_input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
+
+/// The field a placeholder of a `#[display("...")]` template refers to, and the formatting
+/// trait its format spec uses.
+struct Placeholder<'a> {
+    field: &'a DebugField,
+    trait_path: proc_macro2::TokenStream,
+}
+
+/// Rewrites a `#[display("...")]` template so that each placeholder names the variable its field
+/// is bound to, turning `"{host}:{port:>5}"` into `"{__field_0}:{__field_1:>5}"`, and returns it
+/// along with the placeholders found.
+fn parse_template<'a>(
+    template: &LitStr,
+    fields: &'a [DebugField],
+) -> syn::Result<(LitStr, Vec<Placeholder<'a>>)> {
+    let value = template.value();
+    let mut rewritten = String::new();
+    let mut placeholders = vec![];
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        rewritten.push(c);
+        if c == '}' && chars.peek() == Some(&'}') {
+            rewritten.push(chars.next().unwrap());
+        }
+        if c != '{' {
+            continue;
+        }
+        if chars.peek() == Some(&'{') {
+            rewritten.push(chars.next().unwrap());
+            continue;
+        }
+
+        let mut placeholder = String::new();
+        loop {
+            match chars.next() {
+                Some('}') => break,
+                Some(c) => placeholder.push(c),
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        template,
+                        "unterminated placeholder",
+                    ))
+                }
+            }
+        }
+        let (name, spec) = match placeholder.split_once(':') {
+            Some((name, spec)) => (name.trim(), Some(spec)),
+            None => (placeholder.trim(), None),
+        };
+        let field = fields
+            .iter()
+            .find(|f| match &f.member {
+                Member::Named(ident) => ident == name,
+                Member::Unnamed(index) => index.index.to_string() == name,
+            })
+            .ok_or_else(|| {
+                let message = if name.is_empty() {
+                    "placeholders must name a field, like `{name}` or `{0}`".to_owned()
+                } else {
+                    format!("no field `{}` to interpolate", name)
+                };
+                syn::Error::new_spanned(template, message)
+            })?;
+
+        rewritten.push_str(&field.binding.to_string());
+        if let Some(spec) = spec {
+            rewritten.push(':');
+            rewritten.push_str(spec);
+        }
+        rewritten.push('}');
+        placeholders.push(Placeholder {
+            field,
+            trait_path: format_trait(spec.unwrap_or("")),
+        });
+    }
+
+    Ok((LitStr::new(&rewritten, template.span()), placeholders))
+}
+
+/// The formatting trait used by a placeholder with the given format spec, like `Debug` for `{:?}`
+/// or `LowerHex` for `{:#06x}`.
+fn format_trait(spec: &str) -> proc_macro2::TokenStream {
+    if spec.ends_with('?') {
+        return quote!(::core::fmt::Debug);
+    }
+    match spec.chars().last() {
+        Some('x') => quote!(::core::fmt::LowerHex),
+        Some('X') => quote!(::core::fmt::UpperHex),
+        Some('o') => quote!(::core::fmt::Octal),
+        Some('b') => quote!(::core::fmt::Binary),
+        Some('e') => quote!(::core::fmt::LowerExp),
+        Some('E') => quote!(::core::fmt::UpperExp),
+        _ => quote!(::core::fmt::Display),
+    }
+}
+
+/// Finds the `#[display("...")]` template among the attributes of a struct or variant.
+fn get_template(attrs: &[Attribute]) -> syn::Result<Option<LitStr>> {
+    let mut template = None;
+    for a in attrs {
+        if a.path().is_ident("display") {
+            template = Some(a.parse_args::<LitStr>()?);
+        }
+    }
+    Ok(template)
+}
+
+fn expand_display(derive_input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = &derive_input.ident;
+    let variants = get_variants(derive_input, "CustomDisplay")?;
+
+    let mut arms = vec![];
+    let mut predicates: Vec<WherePredicate> = vec![];
+    let is_enum = matches!(derive_input.data, syn::Data::Enum(_));
+    for (variant, attrs, ident) in &variants {
+        let path = &variant.path;
+        let (format, placeholders) = match get_template(attrs)? {
+            Some(template) => parse_template(&template, &variant.fields)?,
+            // unit variants without a template show their name, like `derive(Debug)` does
+            None if is_enum && variant.fields.is_empty() => {
+                (LitStr::new(&variant.name, Span::call_site()), vec![])
+            }
+            None => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!(r#"missing `#[display("...")]` on `{}`"#, ident),
+                ))
+            }
+        };
+
+        for Placeholder { field, trait_path } in &placeholders {
+            predicates.extend(infer_bounds(
+                &derive_input.generics,
+                [&field.ty],
+                trait_path,
+            ));
+        }
+
+        let mut used: Vec<&DebugField> = vec![];
+        for Placeholder { field, .. } in placeholders {
+            if !used.iter().any(|u| u.binding == field.binding) {
+                used.push(field);
+            }
+        }
+        let members = used.iter().map(|f| &f.member);
+        let bindings = used.iter().map(|f| &f.binding);
+        let args = used.iter().map(|f| &f.binding);
+        arms.push(quote!(
+            #path { #(#members: #bindings,)* .. } => ::core::write!(f, #format, #(#args = #args),*),
+        ));
+    }
+
+    let mut generics = derive_input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .extend(dedup_predicates(predicates));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let scrutinee = if variants.is_empty() {
+        quote!(*self)
+    } else {
+        quote!(self)
+    };
+
+    Ok(quote!(
+        #[automatically_derived]
+        impl #impl_generics ::core::fmt::Display for #struct_name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match #scrutinee {
+                    #(#arms)*
+                }
+            }
+        }
+    ))
+}
+
+/// Implements `Display` from `#[display("...")]` templates placed on the struct, or on each
+/// variant of an enum, which interpolate fields by name or, for tuple fields, by index:
+/// ```rust
+/// # use derive_debug::CustomDisplay;
+/// #[derive(CustomDisplay)]
+/// #[display("{host}:{port}")]
+/// struct Addr {
+///     host: String,
+///     port: u16,
+/// }
+/// ```
+/// Only the types of interpolated fields need to implement the formatting trait their
+/// placeholder uses.
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as DeriveInput);
+
+    match expand_display(&derive_input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
@@ -1,8 +1,18 @@
-use proc_macro::TokenStream;
+// Crates that have the "proc-macro" crate type are only allowed to export
+// procedural macros, but the code generated by #[debug(visit)] needs to refer
+// to a trait shared by all derived types. As in derive_builder, the macros live
+// in the separate derive_debug_impl crate and are re-exported from here, so
+// that users only have one crate that they need to import.
+#![no_std]
 
-#[proc_macro_derive(CustomDebug)]
-pub fn derive(input: TokenStream) -> TokenStream {
-    let _ = input;
+pub use derive_debug_impl::{CustomDebug, CustomDisplay};
 
-    unimplemented!()
+use core::fmt::Debug;
+
+/// Receives the fields of a value deriving `CustomDebug` with `#[debug(visit)]`, one call per
+/// field shown by its `Debug` output, via the generated `visit_fields` method.
+pub trait FieldVisitor {
+    /// Called with the name the field is shown under, or its index for tuple fields, and the
+    /// value shown for it, after applying formats, redaction and renames.
+    fn visit_field(&mut self, name: &str, value: &dyn Debug);
 }
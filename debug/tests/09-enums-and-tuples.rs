@@ -0,0 +1,53 @@
+// Error types and state machines are usually enums, and many wrappers are
+// tuple structs or unit structs, so the derive should support all of them.
+//
+// Generate a match on self with one arm per variant. Variants with named fields
+// use debug_struct, tuple variants and tuple structs use debug_tuple, and unit
+// variants and unit structs print just their name, the same as the standard
+// library's derive(Debug). The #[debug = "..."] attribute and the inference of
+// bounds apply to the fields of every variant.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+pub enum State<T> {
+    Idle,
+    Running(u32, #[debug = "{:#x}"] u32),
+    Failed { code: i32, detail: T },
+    Marker(PhantomData<T>),
+}
+
+#[derive(CustomDebug)]
+pub struct Meters(#[debug = "{}m"] f64);
+
+#[derive(CustomDebug)]
+pub struct Unit;
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+#[derive(CustomDebug)]
+pub enum Tagged<T> {
+    Marker(PhantomData<T>),
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    assert_eq!(format!("{:?}", State::<()>::Idle), "Idle");
+    assert_eq!(format!("{:?}", State::<()>::Running(1, 255)), "Running(1, 0xff)");
+    assert_eq!(
+        format!("{:?}", State::Failed { code: -1, detail: "io" }),
+        r#"Failed { code: -1, detail: "io" }"#,
+    );
+    assert_eq!(format!("{:?}", Meters(1.5)), "Meters(1.5m)");
+    assert_eq!(format!("{:?}", Unit), "Unit");
+    assert_eq!(format!("{:#?}", State::<()>::Running(1, 2)), "Running(\n    1,\n    0x2,\n)");
+
+    assert_debug::<Never>();
+    assert_debug::<Tagged<NotDebug>>();
+}
@@ -0,0 +1,61 @@
+// Structs holding tokens and passwords need to be loggable without leaking
+// them.
+//
+// A field marked #[debug(skip)] is left out of the output entirely, which then
+// ends in `..` through finish_non_exhaustive() so that readers know something
+// was hidden. A field marked #[debug(redact)] prints as "***", and
+// #[debug(redact_with = "path")] prints whatever the function returns when
+// given a reference to the field, for example the last few characters.
+//
+// None of these fields are ever formatted with their own Debug impl, so no
+// Debug bound should be inferred for their types.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub struct Credentials {
+    user: String,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact_with = "last_four")]
+    card: String,
+    #[debug(skip)]
+    session: Session,
+}
+
+#[derive(CustomDebug)]
+pub struct Token<S>(u32, #[debug(skip)] S);
+
+#[derive(CustomDebug)]
+pub enum Secret<S> {
+    Hidden(#[debug(redact)] S),
+    Shown { id: u32 },
+}
+
+pub struct Session;
+
+fn last_four(s: &String) -> String {
+    format!("...{}", &s[s.len() - 4..])
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let credentials = Credentials {
+        user: "root".to_owned(),
+        password: "hunter2".to_owned(),
+        card: "4111111111111111".to_owned(),
+        session: Session,
+    };
+    assert_eq!(
+        format!("{:?}", credentials),
+        r#"Credentials { user: "root", password: "***", card: "...1111", .. }"#,
+    );
+    assert_eq!(format!("{:?}", Token(7, Session)), "Token(7, ..)");
+    assert_eq!(format!("{:?}", Secret::Hidden(Session)), r#"Hidden("***")"#);
+    assert_eq!(format!("{:?}", Secret::<Session>::Shown { id: 1 }), "Shown { id: 1 }");
+
+    assert_debug::<Token<Session>>();
+    assert_debug::<Secret<Session>>();
+}
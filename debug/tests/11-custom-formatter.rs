@@ -0,0 +1,62 @@
+// Some fields, like timestamps, raw pointers or byte buffers, need more than a
+// format string to be shown usefully.
+//
+// A field marked #[debug(with = "path")] is written by calling the function at
+// that path with a reference to the field and the formatter, exactly like the
+// body of a Debug impl:
+//
+//     fn fmt_bytes(bytes: &Vec<u8>, f: &mut fmt::Formatter) -> fmt::Result
+//
+// debug_struct() only accepts values implementing Debug, so the generated code
+// passes the field through a small wrapper type whose Debug impl calls the
+// function. The field's own type does not need to implement Debug, and no
+// bound should be inferred for it.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+
+#[derive(CustomDebug)]
+pub struct Packet<P> {
+    id: u16,
+    #[debug(with = "fmt_bytes")]
+    payload: Vec<u8>,
+    #[debug(with = "formatters::opaque")]
+    handle: P,
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper(#[debug(with = "formatters::opaque")] Opaque);
+
+pub struct Opaque;
+
+fn fmt_bytes(bytes: &Vec<u8>, f: &mut fmt::Formatter) -> fmt::Result {
+    for b in bytes {
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+mod formatters {
+    use std::fmt;
+
+    pub fn opaque<T>(_: &T, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("<opaque>")
+    }
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let packet = Packet {
+        id: 3,
+        payload: vec![0xde, 0xad, 0x01],
+        handle: Opaque,
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { id: 3, payload: dead01, handle: <opaque> }",
+    );
+    assert_eq!(format!("{:?}", Wrapper(Opaque)), "Wrapper(<opaque>)");
+
+    assert_debug::<Packet<Opaque>>();
+}
@@ -0,0 +1,49 @@
+// Structs holding large collections or byte buffers flood the logs when they
+// are printed in full.
+//
+// A field marked #[debug(max_items = N)] shows only the first N items of
+// anything that can be iterated by reference, followed by an entry saying how
+// many more there were. A field marked #[debug(len_only)] shows only the
+// result of calling len() on it, and a field marked #[debug(hex)] shows a
+// [u8] or Vec<u8> as one compact hex string.
+//
+// Only the max_items fields still need their items to implement Debug.
+
+use derive_debug::CustomDebug;
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub struct Batch<T> {
+    #[debug(max_items = 3)]
+    ids: Vec<u32>,
+    #[debug(max_items = 2)]
+    tags: BTreeSet<&'static str>,
+    #[debug(len_only)]
+    rows: Vec<T>,
+    #[debug(hex)]
+    checksum: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub struct Frame<'a>(#[debug(hex)] &'a [u8], #[debug(max_items = 0)] [u16; 2]);
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    let batch = Batch {
+        ids: (1..=10).collect(),
+        tags: ["a", "b"].into_iter().collect(),
+        rows: vec![NotDebug, NotDebug],
+        checksum: vec![0xca, 0xfe, 0x00, 0x1f],
+    };
+    assert_eq!(
+        format!("{:?}", batch),
+        r#"Batch { ids: [1, 2, 3, .. (7 more)], tags: ["a", "b"], rows: 2, checksum: cafe001f }"#,
+    );
+    assert_eq!(format!("{:?}", Frame(&[1, 255], [7, 8])), "Frame(01ff, [.. (2 more)])");
+
+    assert_debug::<Batch<NotDebug>>();
+}
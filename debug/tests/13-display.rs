@@ -0,0 +1,68 @@
+// Alongside CustomDebug, the crate provides a Display derive driven by a
+// template placed on the struct, or on each variant of an enum:
+//
+//     #[derive(CustomDisplay)]
+//     #[display("{host}:{port}")]
+//     pub struct Addr {
+//         host: String,
+//         port: u16,
+//     }
+//
+// Placeholders name a field, or give the index of a tuple field, and may carry
+// any format spec that write! accepts. Unit variants without a template show
+// their name.
+//
+// Bounds are inferred the same way as for CustomDebug, but only for the fields
+// that are interpolated, and with the trait their placeholder uses: Display for
+// {name}, Debug for {name:?}, LowerHex for {name:x} and so on.
+
+use derive_debug::CustomDisplay;
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+#[derive(CustomDisplay)]
+#[display("{host}:{port}")]
+pub struct Addr {
+    host: String,
+    port: u16,
+}
+
+#[derive(CustomDisplay)]
+#[display("{0:>6.2}{1}")]
+pub struct Measure<U>(f64, U);
+
+#[derive(CustomDisplay)]
+pub enum Event<T, K> {
+    #[display("key {key:?} pressed {count} times ({count:#x})")]
+    Key { key: K, count: u32, marker: PhantomData<T> },
+    #[display("{{resize}} to {0}x{1}")]
+    Resize(u16, u16, T),
+    Closed,
+}
+
+fn assert_display<F: Display>() {}
+
+fn main() {
+    struct NotDisplay;
+
+    let addr = Addr {
+        host: "localhost".to_owned(),
+        port: 8080,
+    };
+    assert_eq!(addr.to_string(), "localhost:8080");
+    assert_eq!(Measure(1.5, "m").to_string(), "  1.50m");
+
+    let key = Event::<NotDisplay, char>::Key {
+        key: 'q',
+        count: 10,
+        marker: PhantomData,
+    };
+    assert_eq!(key.to_string(), "key 'q' pressed 10 times (0xa)");
+    assert_eq!(
+        Event::<_, ()>::Resize(80, 24, NotDisplay).to_string(),
+        "{resize} to 80x24",
+    );
+    assert_eq!(Event::<NotDisplay, ()>::Closed.to_string(), "Closed");
+
+    assert_display::<Event<NotDisplay, char>>();
+}
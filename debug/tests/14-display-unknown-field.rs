@@ -0,0 +1,13 @@
+// A placeholder that names no field of the struct should be reported against
+// the template.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+#[display("{host}:{prot}")]
+pub struct Addr {
+    host: String,
+    port: u16,
+}
+
+fn main() {}
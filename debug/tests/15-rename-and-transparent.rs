@@ -0,0 +1,70 @@
+// Structured log lines should stay short and use the names the readers of the
+// logs know.
+//
+// #[debug(rename = "...")] shows a field under another name, and
+// #[debug(name = "...")] does the same for the struct, or for a variant when
+// placed on it. #[debug(skip_if = "path")] leaves a field out whenever the
+// predicate returns true for it, like Option::is_none for empty optional
+// fields; unlike #[debug(skip)] this does not make the output non-exhaustive.
+//
+// #[debug(transparent)] on a struct with a single field prints that field's
+// value directly, as if the wrapper were not there.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(name = "Req")]
+pub struct HttpRequest {
+    #[debug(rename = "m")]
+    method: &'static str,
+    path: String,
+    #[debug(skip_if = "Option::is_none")]
+    query: Option<String>,
+    #[debug(skip_if = "Vec::is_empty", rename = "h")]
+    headers: Vec<(String, String)>,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Mask {
+    #[debug = "{:#06b}"]
+    bits: u8,
+}
+
+#[derive(CustomDebug)]
+pub enum Response {
+    #[debug(name = "200")]
+    Ok(#[debug(skip_if = "str::is_empty")] &'static str),
+    NotFound,
+}
+
+fn main() {
+    let request = HttpRequest {
+        method: "GET",
+        path: "/".to_owned(),
+        query: None,
+        headers: vec![],
+    };
+    assert_eq!(format!("{:?}", request), r#"Req { m: "GET", path: "/" }"#);
+
+    let request = HttpRequest {
+        query: Some("q=1".to_owned()),
+        headers: vec![("a".to_owned(), "b".to_owned())],
+        ..request
+    };
+    assert_eq!(
+        format!("{:?}", request),
+        r#"Req { m: "GET", path: "/", query: Some("q=1"), h: [("a", "b")] }"#,
+    );
+
+    assert_eq!(format!("{:?}", UserId(7)), "7");
+    assert_eq!(format!("{:?}", Some(UserId(7))), "Some(7)");
+    assert_eq!(format!("{:?}", Mask { bits: 5 }), "0b0101");
+    assert_eq!(format!("{:?}", Response::Ok("")), "200");
+    assert_eq!(format!("{:?}", Response::Ok("body")), r#"200("body")"#);
+    assert_eq!(format!("{:?}", Response::NotFound), "NotFound");
+}
@@ -0,0 +1,81 @@
+// Refinements of the bound inference from tests 04 through 08.
+//
+// A #[debug(bound = "...")] attribute on a single field replaces only the
+// bounds inferred from that field's type, keeping those of the other fields.
+//
+// Associated types are found wherever they are nested inside other types, as
+// in Box<T::Value> or HashMap<K, Vec<T::Value>>, and fully qualified ones like
+// <T as Other>::Output get a bound of their own rather than one on T. Type
+// parameters that only appear in function pointers, like fn(T) or
+// PhantomData<fn() -> T>, need no bound at all since function pointers are
+// always Debug.
+//
+// The same bound inferred from several fields is only emitted once.
+
+use derive_debug::CustomDebug;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+pub trait Trait {
+    type Value;
+}
+
+pub trait Other {
+    type Output;
+}
+
+#[derive(CustomDebug)]
+pub struct Overridden<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+#[derive(CustomDebug)]
+pub struct Nested<T: Trait + Other, K> {
+    boxed: Box<T::Value>,
+    map: HashMap<K, Vec<T::Value>>,
+    qualified: Option<<T as Other>::Output>,
+    again: K,
+}
+
+#[derive(CustomDebug)]
+pub struct Callbacks<A, R> {
+    callback: fn(A) -> R,
+    marker: PhantomData<fn() -> A>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    impl Trait for NotDebug {
+        type Value = u8;
+    }
+
+    impl Other for NotDebug {
+        type Output = &'static str;
+    }
+
+    assert_debug::<Overridden<NotDebug, u8>>();
+    assert_debug::<Nested<NotDebug, u8>>();
+    assert_debug::<Callbacks<NotDebug, NotDebug>>();
+
+    let nested = Nested::<NotDebug, char> {
+        boxed: Box::new(1),
+        map: HashMap::new(),
+        qualified: Some("q"),
+        again: 'k',
+    };
+    assert_eq!(
+        format!("{:?}", nested),
+        r#"Nested { boxed: 1, map: {}, qualified: Some("q"), again: 'k' }"#,
+    );
+}
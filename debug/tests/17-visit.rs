@@ -0,0 +1,69 @@
+// Log backends that emit JSON or key/value pairs want the same fields the
+// Debug output shows, without reimplementing the rules of the derive.
+//
+// With #[debug(visit)] on the type, the derive also generates
+//
+//     pub fn visit_fields(&self, visitor: &mut dyn FieldVisitor)
+//
+// which calls the visitor once per field shown by the Debug output, with the
+// name it is shown under and the value shown for it. Skipped fields are not
+// visited, and format strings, redaction and renames apply just as they do in
+// the Debug output. Tuple fields are visited under their index.
+//
+// The FieldVisitor trait is exported by the derive_debug crate, next to the
+// derive macros.
+
+use derive_debug::{CustomDebug, FieldVisitor};
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+#[debug(visit)]
+pub struct Login {
+    #[debug(rename = "username")]
+    user: String,
+    #[debug(redact)]
+    password: String,
+    #[debug = "{}ms"]
+    latency: u32,
+    #[debug(skip)]
+    attempts: u8,
+    #[debug(skip_if = "Option::is_none")]
+    reason: Option<&'static str>,
+}
+
+#[derive(CustomDebug)]
+#[debug(visit)]
+pub enum Event<T> {
+    Tick(u64, T),
+    Stop,
+}
+
+#[derive(Default)]
+struct Collect(Vec<String>);
+
+impl FieldVisitor for Collect {
+    fn visit_field(&mut self, name: &str, value: &dyn Debug) {
+        self.0.push(format!("{}={:?}", name, value));
+    }
+}
+
+fn main() {
+    let login = Login {
+        user: "root".to_owned(),
+        password: "hunter2".to_owned(),
+        latency: 12,
+        attempts: 3,
+        reason: None,
+    };
+    let mut collect = Collect::default();
+    login.visit_fields(&mut collect);
+    assert_eq!(
+        collect.0,
+        [r#"username="root""#, r#"password="***""#, "latency=12ms"],
+    );
+
+    let mut collect = Collect::default();
+    Event::Tick(5, 'x').visit_fields(&mut collect);
+    Event::<char>::Stop.visit_fields(&mut collect);
+    assert_eq!(collect.0, ["0=5", "1='x'"]);
+}
@@ -0,0 +1,120 @@
+// The derives should work in #![no_std] crates, and in modules where the names
+// of the prelude and of std::fmt mean something else, in the spirit of
+// builder/tests/09-redefined-prelude-types.rs.
+//
+// Refer to everything in the generated code through absolute paths into core,
+// such as ::core::fmt::Formatter, never through std or through names that the
+// caller's code could shadow, and mark the generated impls
+// #[automatically_derived] like the standard library's derives do.
+//
+// std is only linked for the test's runtime, under no name, so that generated
+// paths into ::std fail to resolve, and the output is checked by formatting
+// into a fixed buffer through core::fmt::Write.
+
+#![no_std]
+
+extern crate std as _;
+
+mod shadowed {
+    use derive_debug::{CustomDebug, CustomDisplay};
+
+    pub mod fmt {}
+    pub mod core {}
+    pub trait Debug {}
+    pub trait Display {}
+    pub trait Iterator {}
+    pub trait IntoIterator {}
+    pub type Result = ();
+    pub type Option = ();
+    pub type Some = ();
+    pub type Ok = ();
+
+    #[allow(non_upper_case_globals)]
+    pub const Some: () = ();
+    #[allow(non_upper_case_globals)]
+    pub const None: () = ();
+    #[allow(non_upper_case_globals)]
+    pub const Ok: () = ();
+    #[allow(non_upper_case_globals)]
+    pub const Err: () = ();
+
+    #[derive(CustomDebug, CustomDisplay)]
+    #[debug(visit)]
+    #[display("{id}: {name}")]
+    pub struct Record<'a, T> {
+        pub id: T,
+        pub name: &'a str,
+        #[debug(hex)]
+        pub bytes: [u8; 2],
+        #[debug(max_items = 1)]
+        pub tags: [u8; 3],
+        #[debug(with = "crate::fmt_unit")]
+        pub unit: (),
+    }
+
+    #[derive(CustomDebug, CustomDisplay)]
+    pub enum Kind {
+        #[display("a={0}")]
+        A(u8),
+        B,
+    }
+}
+
+fn fmt_unit(_: &(), f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+    f.write_str("unit")
+}
+
+struct Buffer {
+    bytes: [u8; 128],
+    len: usize,
+}
+
+impl ::core::fmt::Write for Buffer {
+    fn write_str(&mut self, s: &str) -> ::core::fmt::Result {
+        let end = self.len + s.len();
+        let dest = self.bytes.get_mut(self.len..end).ok_or(::core::fmt::Error)?;
+        dest.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+macro_rules! format {
+    ($($arg:tt)*) => {{
+        let mut buffer = Buffer {
+            bytes: [0; 128],
+            len: 0,
+        };
+        ::core::fmt::Write::write_fmt(&mut buffer, ::core::format_args!($($arg)*)).unwrap();
+        buffer
+    }};
+}
+
+impl Buffer {
+    fn as_str(&self) -> &str {
+        ::core::str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+}
+
+fn main() {
+    let record = shadowed::Record {
+        id: 1,
+        name: "one",
+        bytes: [0xab, 0xcd],
+        tags: [1, 2, 3],
+        unit: (),
+    };
+    assert_eq!(
+        format!("{:?}", record).as_str(),
+        r#"Record { id: 1, name: "one", bytes: abcd, tags: [1, .. (2 more)], unit: unit }"#,
+    );
+    assert_eq!(format!("{}", record).as_str(), "1: one");
+    assert_eq!(
+        format!("{:?} {}", shadowed::Kind::A(2), shadowed::Kind::A(2)).as_str(),
+        "A(2) a=2",
+    );
+    assert_eq!(
+        format!("{:?} {}", shadowed::Kind::B, shadowed::Kind::B).as_str(),
+        "B B",
+    );
+}
@@ -1,12 +1,22 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse.rs");
-    //t.pass("tests/02-impl-debug.rs");
-    //t.pass("tests/03-custom-format.rs");
-    //t.pass("tests/04-type-parameter.rs");
-    //t.pass("tests/05-phantom-data.rs");
-    //t.pass("tests/06-bound-trouble.rs");
-    //t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/01-parse.rs");
+    t.pass("tests/02-impl-debug.rs");
+    t.pass("tests/03-custom-format.rs");
+    t.pass("tests/04-type-parameter.rs");
+    t.pass("tests/05-phantom-data.rs");
+    t.pass("tests/06-bound-trouble.rs");
+    t.pass("tests/07-associated-type.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enums-and-tuples.rs");
+    t.pass("tests/10-redact-and-skip.rs");
+    t.pass("tests/11-custom-formatter.rs");
+    t.pass("tests/12-collections.rs");
+    t.pass("tests/13-display.rs");
+    t.compile_fail("tests/14-display-unknown-field.rs");
+    t.pass("tests/15-rename-and-transparent.rs");
+    t.pass("tests/16-field-bounds.rs");
+    t.pass("tests/17-visit.rs");
+    t.pass("tests/18-no-std.rs");
 }
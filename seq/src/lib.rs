@@ -1,8 +1,733 @@
 use proc_macro::TokenStream;
+use proc_macro2::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenTree};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{braced, bracketed, parenthesized, parse_macro_input, token, Token};
+use syn::{BinOp, Expr, ExprLit, ExprUnary, Lit, LitInt, RangeLimits, UnOp};
 
+/// A parsed invocation, like `seq!(N in 0..8 { ... })` or `seq!(N in 0..4, M in 0..2 { ... })`.
+struct Seq {
+    /// The variable counting the repetitions, declared like `seq!(i, T in [...] { ... })`
+    index: Option<Var>,
+    /// Each loop variable with the values it takes
+    vars: Vec<Var>,
+    /// Everything inside the braces
+    body: proc_macro2::TokenStream,
+}
+
+/// The loop variables declared by one part of the header, like `N in 0..8`, `T in [u8, u16]`
+/// or `(T, BITS) in [(u8, 8), (u16, 16)]`.
+struct Var {
+    /// The variables, `N` or `T` and `BITS`
+    idents: Vec<Ident>,
+    /// The values taken by the variables, in order, with one value for each variable
+    values: Vec<Vec<Value>>,
+    /// How integer values are written when substituted
+    form: LitForm,
+}
+
+/// A value taken by a loop variable.
+enum Value {
+    /// An integer from a range
+    Int(i128),
+    /// An element of a list, like `u8`
+    Tokens(proc_macro2::TokenStream),
+}
+
+/// The value of one loop variable during one repetition of the body.
+#[derive(Clone, Copy)]
+struct Binding<'a> {
+    ident: &'a Ident,
+    value: &'a Value,
+    form: &'a LitForm,
+}
+
+/// The value of each loop variable during one repetition of the body.
+type Iteration<'a> = Vec<Binding<'a>>;
+
+/// How the values of a loop variable are written when substituted, copied from the first
+/// integer literal of its range, so that `0x00u8..0x10u8` produces `0x00u8`, `0x01u8` and so on.
+#[derive(Default)]
+struct LitForm {
+    /// The type suffix, like `u8`, or empty for unsuffixed literals
+    suffix: String,
+    /// The prefix of non-decimal literals, like `0x`, or empty for decimal ones
+    prefix: String,
+    /// The number of digits the value is padded to with zeros, for non-decimal literals
+    digits: usize,
+    /// Whether hexadecimal digits are written in uppercase
+    uppercase: bool,
+}
+
+impl LitForm {
+    fn new(lit: &LitInt) -> Self {
+        let repr = lit.to_string().replace('_', "");
+        let unsuffixed = &repr[..repr.len() - lit.suffix().len()];
+        let prefix = ["0x", "0o", "0b"]
+            .into_iter()
+            .find(|prefix| unsuffixed.starts_with(prefix))
+            .unwrap_or("");
+        let digits = &unsuffixed[prefix.len()..];
+        Self {
+            suffix: lit.suffix().to_owned(),
+            prefix: prefix.to_owned(),
+            digits: if prefix.is_empty() { 0 } else { digits.len() },
+            uppercase: digits.bytes().any(|b| b.is_ascii_uppercase()),
+        }
+    }
+
+    /// Writes `value` as a literal in this form, with the given span. Negative values are
+    /// parenthesized, so that `N.pow(2)` applies to the whole value rather than only to its
+    /// digits.
+    fn token(&self, value: i128, span: Span) -> TokenTree {
+        let magnitude = value.unsigned_abs();
+        let digits = match (self.prefix.as_str(), self.uppercase) {
+            ("0x", false) => format!("{:01$x}", magnitude, self.digits),
+            ("0x", true) => format!("{:01$X}", magnitude, self.digits),
+            ("0o", _) => format!("{:01$o}", magnitude, self.digits),
+            ("0b", _) => format!("{:01$b}", magnitude, self.digits),
+            _ => magnitude.to_string(),
+        };
+        let sign = if value < 0 { "-" } else { "" };
+        let repr = format!("{}{}{}{}", sign, self.prefix, digits, self.suffix);
+        let mut literal: Literal = repr.parse().expect("integer literals are valid tokens");
+        literal.set_span(span);
+        if value >= 0 {
+            return TokenTree::Literal(literal);
+        }
+        let literal = proc_macro2::TokenStream::from(TokenTree::Literal(literal));
+        let mut group = Group::new(Delimiter::Parenthesis, literal);
+        group.set_span(span);
+        TokenTree::Group(group)
+    }
+}
+
+/// Parses one part of the header: a loop variable and its range, like `N in 0..8`,
+/// `N in 16..=20` or `N in (0..64).step_by(8)`, or loop variables and a list of values, like
+/// `T in [u8, u16]` or `(T, BITS) in [(u8, 8), (u16, 16)]`.
+fn parse_var(input: ParseStream) -> syn::Result<Var> {
+    let idents: Vec<Ident> = if input.peek(token::Paren) {
+        let content;
+        parenthesized!(content in input);
+        let idents = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+        idents.into_iter().collect()
+    } else {
+        vec![input.parse()?]
+    };
+    input.parse::<Token![in]>()?;
+
+    if input.peek(token::Bracket) {
+        let content;
+        let brackets = bracketed!(content in input);
+        let values = parse_list(&content, idents.len())?;
+        if values.is_empty() {
+            return Err(syn::Error::new(
+                brackets.span.join(),
+                "expected a non-empty list",
+            ));
+        }
+        return Ok(Var {
+            idents,
+            values,
+            form: LitForm::default(),
+        });
+    }
+
+    if idents.len() != 1 {
+        return Err(input.error("expected a list of tuples, like `[(u8, 8), (u16, 16)]`"));
+    }
+    let range = Expr::parse_without_eager_brace(input)?;
+    let values = eval_range(&range)?;
+    let form = find_literal(&range).map(LitForm::new).unwrap_or_default();
+    Ok(Var {
+        idents,
+        values: values.into_iter().map(|v| vec![Value::Int(v)]).collect(),
+        form,
+    })
+}
+
+/// Parses the comma-separated elements of a list, each giving the values of `len` variables:
+/// any tokens for a single variable, or a parenthesized tuple of that many values.
+fn parse_list(input: ParseStream, len: usize) -> syn::Result<Vec<Vec<Value>>> {
+    let mut values = vec![];
+    for element in split_commas(input.parse()?) {
+        if len == 1 {
+            values.push(vec![Value::Tokens(element)]);
+            continue;
+        }
+        let fields = match element.clone().into_iter().collect::<Vec<_>>().as_slice() {
+            [TokenTree::Group(tuple)] if tuple.delimiter() == Delimiter::Parenthesis => {
+                split_commas(tuple.stream())
+            }
+            _ => vec![],
+        };
+        if fields.len() != len {
+            return Err(syn::Error::new_spanned(
+                element,
+                format!("expected a tuple of {} values", len),
+            ));
+        }
+        values.push(fields.into_iter().map(Value::Tokens).collect());
+    }
+    Ok(values)
+}
+
+/// Splits `tokens` at the commas outside of any group, dropping a trailing comma.
+fn split_commas(tokens: proc_macro2::TokenStream) -> Vec<proc_macro2::TokenStream> {
+    let mut parts = vec![proc_macro2::TokenStream::new()];
+    for tt in tokens {
+        match &tt {
+            TokenTree::Punct(comma) if comma.as_char() == ',' => {
+                parts.push(proc_macro2::TokenStream::new())
+            }
+            _ => parts.last_mut().unwrap().extend([tt]),
+        }
+    }
+    if parts.last().unwrap().is_empty() {
+        parts.pop();
+    }
+    parts
+}
+
+/// Finds the first integer literal written in `expr`, usually the start of the range.
+fn find_literal(expr: &Expr) -> Option<&LitInt> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => Some(lit),
+        Expr::Range(range) => range
+            .start
+            .as_deref()
+            .and_then(find_literal)
+            .or_else(|| range.end.as_deref().and_then(find_literal)),
+        Expr::Paren(paren) => find_literal(&paren.expr),
+        Expr::Group(group) => find_literal(&group.expr),
+        Expr::Unary(unary) => find_literal(&unary.expr),
+        Expr::Binary(binary) => find_literal(&binary.left).or_else(|| find_literal(&binary.right)),
+        Expr::MethodCall(call) => find_literal(&call.receiver),
+        _ => None,
+    }
+}
+
+/// Evaluates the values of a range expression: a range whose bounds are constant arithmetic,
+/// like `0..2*8`, optionally followed by calls to `.step_by(n)` and `.rev()`.
+fn eval_range(expr: &Expr) -> syn::Result<Vec<i128>> {
+    match expr {
+        Expr::Range(range) => {
+            let (Some(start), Some(end)) = (&range.start, &range.end) else {
+                return Err(syn::Error::new_spanned(range, "expected a bounded range"));
+            };
+            let start = eval_int(start)?;
+            let end = eval_int(end)?;
+            Ok(match range.limits {
+                RangeLimits::HalfOpen(_) => (start..end).collect(),
+                RangeLimits::Closed(_) => (start..=end).collect(),
+            })
+        }
+        Expr::Paren(paren) => eval_range(&paren.expr),
+        Expr::Group(group) => eval_range(&group.expr),
+        Expr::MethodCall(call) if call.method == "rev" && call.args.is_empty() => {
+            let mut values = eval_range(&call.receiver)?;
+            values.reverse();
+            Ok(values)
+        }
+        Expr::MethodCall(call) if call.method == "step_by" && call.args.len() == 1 => {
+            let values = eval_range(&call.receiver)?;
+            let step = match usize::try_from(eval_int(&call.args[0])?) {
+                Ok(step) if step > 0 => step,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &call.args,
+                        "expected a positive step",
+                    ))
+                }
+            };
+            Ok(values.into_iter().step_by(step).collect())
+        }
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "expected a range like `0..8`, optionally followed by `.step_by(n)` or `.rev()`",
+        )),
+    }
+}
+
+/// Evaluates constant integer arithmetic, like `2 * 8` or `(1 << 4) - 1`.
+fn eval_int(expr: &Expr) -> syn::Result<i128> {
+    let value =
+        match expr {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(lit), ..
+            }) => Some(lit.base10_parse()?),
+            Expr::Paren(paren) => Some(eval_int(&paren.expr)?),
+            Expr::Group(group) => Some(eval_int(&group.expr)?),
+            Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_),
+                expr,
+                ..
+            }) => eval_int(expr)?.checked_neg(),
+            Expr::Binary(binary) => {
+                let left = eval_int(&binary.left)?;
+                let right = eval_int(&binary.right)?;
+                match binary.op {
+                    BinOp::Add(_) => left.checked_add(right),
+                    BinOp::Sub(_) => left.checked_sub(right),
+                    BinOp::Mul(_) => left.checked_mul(right),
+                    BinOp::Div(_) => left.checked_div(right),
+                    BinOp::Rem(_) => left.checked_rem(right),
+                    BinOp::Shl(_) => u32::try_from(right).ok().and_then(|r| left.checked_shl(r)),
+                    BinOp::Shr(_) => u32::try_from(right).ok().and_then(|r| left.checked_shr(r)),
+                    BinOp::BitAnd(_) => Some(left & right),
+                    BinOp::BitOr(_) => Some(left | right),
+                    BinOp::BitXor(_) => Some(left ^ right),
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            binary.op,
+                            "unsupported operator in a `seq!` range",
+                        ))
+                    }
+                }
+            }
+            _ => return Err(syn::Error::new_spanned(
+                expr,
+                "cannot evaluate this at expansion time, expected integer literals and arithmetic",
+            )),
+        };
+    value.ok_or_else(|| syn::Error::new_spanned(expr, "overflow or division by zero"))
+}
+
+impl Parse for Seq {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut index = None;
+        if input.peek(syn::Ident) && input.peek2(Token![,]) {
+            index = Some(input.parse::<Ident>()?);
+            input.parse::<Token![,]>()?;
+        }
+        let mut vars = vec![parse_var(input)?];
+        while input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            vars.push(parse_var(input)?);
+        }
+        let content;
+        braced!(content in input);
+        let body = content.parse()?;
+
+        let count = vars.iter().map(|var| var.values.len()).product::<usize>();
+        let index = index.map(|ident| Var {
+            idents: vec![ident],
+            values: (0..count as i128).map(|i| vec![Value::Int(i)]).collect(),
+            form: LitForm::default(),
+        });
+        Ok(Self { index, vars, body })
+    }
+}
+
+impl Var {
+    /// Pairs each variable with its value in `values`, one of the elements of `self.values`.
+    fn bindings<'a>(&'a self, values: &'a [Value]) -> impl Iterator<Item = Binding<'a>> {
+        self.idents
+            .iter()
+            .zip(values)
+            .map(|(ident, value)| Binding {
+                ident,
+                value,
+                form: &self.form,
+            })
+    }
+}
+
+impl Seq {
+    /// Returns every combination of the values of the loop variables, with the last variable
+    /// changing fastest, like nested `for` loops written in the same order.
+    fn iterations(&self) -> Vec<Iteration<'_>> {
+        let mut iterations = vec![vec![]];
+        for var in &self.vars {
+            iterations = iterations
+                .into_iter()
+                .flat_map(|iteration: Iteration| {
+                    var.values.iter().map(move |values| {
+                        let mut iteration = iteration.clone();
+                        iteration.extend(var.bindings(values));
+                        iteration
+                    })
+                })
+                .collect();
+        }
+        if let Some(index) = &self.index {
+            for (iteration, values) in iterations.iter_mut().zip(&index.values) {
+                iteration.extend(index.bindings(values));
+            }
+        }
+        iterations
+    }
+
+    /// Returns the body, either repeated once per iteration as a whole, or with only its
+    /// `#(...)*` sections repeated when it has any.
+    fn expand(&self) -> syn::Result<proc_macro2::TokenStream> {
+        let tokens: Vec<TokenTree> = self.body.clone().into_iter().collect();
+        if has_section(&tokens) {
+            return self.expand_sections(&tokens);
+        }
+
+        let mut expanded = proc_macro2::TokenStream::new();
+        for iteration in self.iterations() {
+            expanded.extend(substitute(&tokens, &iteration, &[])?);
+        }
+        Ok(expanded)
+    }
+
+    /// Copies `tokens`, replacing each `#(...)*` section by its contents repeated once per
+    /// iteration, and leaving everything else as written.
+    fn expand_sections(&self, tokens: &[TokenTree]) -> syn::Result<proc_macro2::TokenStream> {
+        let mut expanded = proc_macro2::TokenStream::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            if let Some(section) = get_section(&tokens[i..]) {
+                let contents: Vec<TokenTree> = section.stream().into_iter().collect();
+                for iteration in self.iterations() {
+                    expanded.extend(substitute(&contents, &iteration, &[])?);
+                }
+                i += 3;
+                continue;
+            }
+
+            match &tokens[i] {
+                // sections of a nested `seq!` belong to it
+                TokenTree::Group(group) if !is_nested_seq(&tokens[..i]) => {
+                    let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+                    let stream = self.expand_sections(&inner)?;
+                    expanded.extend([TokenTree::Group(copy_group(group, stream))]);
+                }
+                tt => expanded.extend([tt.clone()]),
+            }
+            i += 1;
+        }
+        Ok(expanded)
+    }
+}
+
+/// Copies `tokens` for one iteration, replacing each loop variable by its value, either a
+/// literal written like those of its range, with the variable's span, or the tokens of its list
+/// element, and pasting `prefix~N` into one
+/// identifier.
+///
+/// `pending` holds the variables of the nested `seq!` invocations `tokens` are part of, which
+/// are left in place, even inside pasted identifiers, for those invocations to replace.
+fn substitute(
+    tokens: &[TokenTree],
+    iteration: &[Binding],
+    pending: &[Ident],
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut expanded = proc_macro2::TokenStream::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Ident(_) if is_paste(&tokens[i..]) => {
+                let (pasted, len) = paste(&tokens[i..], iteration, pending)?;
+                expanded.extend(pasted);
+                i += len;
+                continue;
+            }
+            TokenTree::Ident(ident) => match get_value(ident, iteration, pending) {
+                Some(Binding {
+                    value: Value::Int(value),
+                    form,
+                    ..
+                }) => {
+                    expanded.extend([form.token(*value, ident.span())]);
+                }
+                Some(Binding {
+                    value: Value::Tokens(value),
+                    ..
+                }) => expanded.extend(value.clone()),
+                None => expanded.extend([tokens[i].clone()]),
+            },
+            TokenTree::Group(group) => {
+                let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+                let stream = if is_nested_seq(&tokens[..i]) {
+                    let mut pending = pending.to_vec();
+                    pending.extend(get_header_vars(&inner));
+                    substitute(&inner, iteration, &pending)?
+                } else {
+                    substitute(&inner, iteration, pending)?
+                };
+                expanded.extend([TokenTree::Group(copy_group(group, stream))]);
+            }
+            tt => expanded.extend([tt.clone()]),
+        }
+        i += 1;
+    }
+    Ok(expanded)
+}
+
+/// The value of `ident` in this iteration, if it is one of the loop variables and not one of
+/// a nested `seq!` declaring a variable with the same name.
+fn get_value<'a>(
+    ident: &Ident,
+    iteration: &[Binding<'a>],
+    pending: &[Ident],
+) -> Option<Binding<'a>> {
+    if pending.contains(ident) {
+        return None;
+    }
+    iteration
+        .iter()
+        .find(|binding| binding.ident == ident)
+        .copied()
+}
+
+/// Whether the group following `tokens` holds the input of a nested `seq!`.
+fn is_nested_seq(tokens: &[TokenTree]) -> bool {
+    matches!(
+        tokens,
+        [.., TokenTree::Ident(name), TokenTree::Punct(bang)]
+            if name == "seq" && bang.as_char() == '!'
+    )
+}
+
+/// The loop variables declared by the header of a nested `seq!`: the index variable, and
+/// those written before `in`, alone or in a tuple.
+fn get_header_vars(tokens: &[TokenTree]) -> Vec<Ident> {
+    let mut vars = vec![];
+    if let [TokenTree::Ident(index), TokenTree::Punct(comma), ..] = tokens {
+        if comma.as_char() == ',' {
+            vars.push(index.clone());
+        }
+    }
+    let header = tokens.windows(2).take_while(
+        |pair| !matches!(&pair[0], TokenTree::Group(g) if g.delimiter() == Delimiter::Brace),
+    );
+    for pair in header {
+        match pair {
+            [TokenTree::Ident(var), TokenTree::Ident(keyword)] if keyword == "in" => {
+                vars.push(var.clone());
+            }
+            [TokenTree::Group(tuple), TokenTree::Ident(keyword)] if keyword == "in" => {
+                vars.extend(tuple.stream().into_iter().filter_map(|tt| match tt {
+                    TokenTree::Ident(var) => Some(var),
+                    _ => None,
+                }));
+            }
+            _ => {}
+        }
+    }
+    vars
+}
+
+/// Pastes a chain like `Irq~N`, `f~N~_suffix` or `Reg~N~_~M` at the start of `tokens` into a
+/// single identifier with the span of the first one, returning it with the number of tokens
+/// used.
+///
+/// Variables of nested `seq!` invocations are kept in the chain, so that for example
+/// `Reg~N~_~M` becomes `Reg0_~M` when only `N` is known, for the nested invocation to finish.
+fn paste(
+    tokens: &[TokenTree],
+    iteration: &[Binding],
+    pending: &[Ident],
+) -> syn::Result<(proc_macro2::TokenStream, usize)> {
+    let span = tokens[0].span();
+    // the chain as runs of pasted text separated by the variables left pending
+    let mut parts: Vec<Result<String, Vec<TokenTree>>> = vec![];
+    let mut len = 0;
+    loop {
+        let padding = get_padding(&tokens[len + 1..]);
+        let text = match &tokens[len] {
+            TokenTree::Ident(ident) if pending.contains(ident) => {
+                let used = if padding.is_some() { 3 } else { 1 };
+                parts.push(Err(tokens[len..len + used].to_vec()));
+                None
+            }
+            TokenTree::Ident(ident) => match get_value(ident, iteration, pending) {
+                Some(binding) => Some(paste_value(ident, binding.value, padding)?),
+                None if padding.is_some() => return Err(padding_error(&tokens[len + 1])),
+                None => Some(ident.to_string()),
+            },
+            _ if padding.is_some() => return Err(padding_error(&tokens[len + 1])),
+            tt => Some(tt.to_string()),
+        };
+        if let Some(text) = text {
+            match parts.last_mut() {
+                Some(Ok(run)) => run.push_str(&text),
+                _ => parts.push(Ok(text)),
+            }
+        }
+        len += if padding.is_some() { 3 } else { 1 };
+        if !is_paste(&tokens[len - 1..]) {
+            break;
+        }
+        len += 1;
+    }
+
+    let mut pasted = proc_macro2::TokenStream::new();
+    for (i, part) in parts.into_iter().enumerate() {
+        if i > 0 {
+            pasted.extend([TokenTree::Punct(Punct::new('~', Spacing::Alone))]);
+        }
+        let tt = match part {
+            Err(var) => {
+                pasted.extend(var);
+                continue;
+            }
+            // later parts of a chain may be plain numbers, which the nested `seq!` pastes too
+            Ok(text) if i > 0 && text.bytes().all(|b| b.is_ascii_digit()) => {
+                let mut literal: Literal = text.parse().expect("digits form a literal");
+                literal.set_span(span);
+                TokenTree::Literal(literal)
+            }
+            Ok(text) => {
+                if !text.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+                    return Err(syn::Error::new(
+                        span,
+                        format!("`{}` is not a valid identifier", text),
+                    ));
+                }
+                TokenTree::Ident(Ident::new(&text, span))
+            }
+        };
+        pasted.extend([tt]);
+    }
+    Ok((pasted, len))
+}
+
+/// The text a variable's value contributes to a pasted identifier: the decimal digits of an
+/// integer, padded with zeros to `padding`, or the text of a single identifier or literal.
+fn paste_value(ident: &Ident, value: &Value, padding: Option<usize>) -> syn::Result<String> {
+    match value {
+        Value::Int(value) if *value < 0 => Err(syn::Error::new(
+            ident.span(),
+            format!(
+                "cannot paste the negative value {} into an identifier",
+                value
+            ),
+        )),
+        Value::Int(value) => Ok(format!("{:01$}", value, padding.unwrap_or(0))),
+        Value::Tokens(_) if padding.is_some() => Err(syn::Error::new(
+            ident.span(),
+            format!("cannot pad `{}`, which does not take integer values", ident),
+        )),
+        Value::Tokens(tokens) => match tokens.clone().into_iter().collect::<Vec<_>>().as_slice() {
+            [tt @ (TokenTree::Ident(_) | TokenTree::Literal(_))] => Ok(tt.to_string()),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                format!("cannot paste `{}` into an identifier", tokens),
+            )),
+        },
+    }
+}
+
+/// The width given after a pasted variable written like `Reg~N:02`, if `tokens` start with
+/// the `:02` part, to which the value is padded with zeros.
+fn get_padding(tokens: &[TokenTree]) -> Option<usize> {
+    match tokens {
+        [TokenTree::Punct(colon), TokenTree::Literal(width), ..]
+            if colon.as_char() == ':' && colon.spacing() == Spacing::Alone =>
+        {
+            let width = width.to_string();
+            if width.len() > 1 && width.starts_with('0') {
+                width.parse().ok()
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The error for padding, starting at `colon`, given after something other than a loop
+/// variable.
+fn padding_error(colon: &TokenTree) -> syn::Error {
+    syn::Error::new(
+        colon.span(),
+        "padding like `:02` only applies to pasted loop variables",
+    )
+}
+
+/// Whether `tokens` start with an identifier or literal followed by `~` and another
+/// identifier or literal.
+fn is_paste(tokens: &[TokenTree]) -> bool {
+    matches!(
+        tokens,
+        [
+            TokenTree::Ident(_) | TokenTree::Literal(_),
+            TokenTree::Punct(tilde),
+            TokenTree::Ident(_) | TokenTree::Literal(_),
+            ..
+        ] if tilde.as_char() == '~'
+    )
+}
+
+/// Returns the parenthesized group if `tokens` start with a `#(...)*` section.
+fn get_section(tokens: &[TokenTree]) -> Option<&Group> {
+    match tokens {
+        [TokenTree::Punct(pound), TokenTree::Group(group), TokenTree::Punct(star), ..]
+            if pound.as_char() == '#'
+                && pound.spacing() == Spacing::Alone
+                && group.delimiter() == Delimiter::Parenthesis
+                && star.as_char() == '*' =>
+        {
+            Some(group)
+        }
+        _ => None,
+    }
+}
+
+/// Whether `tokens` contain a `#(...)*` section at any depth, outside of nested `seq!`
+/// invocations.
+fn has_section(tokens: &[TokenTree]) -> bool {
+    (0..tokens.len()).any(|i| match &tokens[i] {
+        _ if get_section(&tokens[i..]).is_some() => true,
+        TokenTree::Group(group) if !is_nested_seq(&tokens[..i]) => {
+            let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+            has_section(&inner)
+        }
+        _ => false,
+    })
+}
+
+/// Creates a group with the delimiter and span of `group` around new contents.
+fn copy_group(group: &Group, stream: proc_macro2::TokenStream) -> Group {
+    let mut copy = Group::new(group.delimiter(), stream);
+    copy.set_span(group.span());
+    copy
+}
+
+/// Repeats a block of code once per value of a loop variable:
+/// ```rust
+/// # use seq::seq;
+/// seq!(N in 0..4 {
+///     fn f~N() -> u64 {
+///         N * 2
+///     }
+/// });
+///
+/// assert_eq!(f3(), 6);
+/// ```
+/// The variable is replaced by each value in turn, and pasted into identifiers written like
+/// `f~N`. When the block contains `#(...)*` sections, only those are repeated.
+///
+/// Variables can also take their values from a list of tokens, destructuring tuples to bind
+/// several variables at once, with an optional leading variable counting the repetitions:
+/// ```rust
+/// # use seq::seq;
+/// # pub trait Bits { const BITS: u32; }
+/// seq!(i, (T, N) in [(u8, 8), (u16, 16)] {
+///     impl Bits for T {
+///         const BITS: u32 = N;
+///     }
+///
+///     fn index_~T() -> usize {
+///         i
+///     }
+/// });
+///
+/// assert_eq!(<u16 as Bits>::BITS, 16);
+/// assert_eq!(index_u16(), 1);
+/// ```
 #[proc_macro]
 pub fn seq(input: TokenStream) -> TokenStream {
-    let _ = input;
+    let seq = parse_macro_input!(input as Seq);
 
-    unimplemented!()
+    match seq.expand() {
+        Ok(ts) => ts.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
 }
@@ -0,0 +1,34 @@
+// The optional features mentioned in tests 04 and 05.
+//
+// Identifiers can be pasted with text after the loop variable as well as
+// before it, as in `f~N~_suffix`, and a body can have several separate #(...)*
+// sections, each repeated according to the same loop bounds while the tokens
+// between them are written once.
+
+use seq::seq;
+
+seq!(N in 0..3 {
+    fn f~N~_suffix() -> u64 {
+        N
+    }
+});
+
+seq!(N in 1..4 {
+    struct Sizes {
+        #(
+            size~N: usize,
+        )*
+    }
+
+    const SIZES: Sizes = Sizes {
+        #(
+            size~N: N * 8,
+        )*
+    };
+});
+
+fn main() {
+    assert_eq!(f0_suffix() + f1_suffix() + f2_suffix(), 3);
+    assert_eq!(SIZES.size1 + SIZES.size3, 32);
+    let _ = SIZES.size2;
+}
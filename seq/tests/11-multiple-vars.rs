@@ -0,0 +1,64 @@
+// Several loop variables can be declared in one header, separated by commas.
+// The body is repeated for every combination of their values, with the last
+// variable changing fastest, as with nested for loops written in the same
+// order:
+//
+//     seq!(N in 0..4, M in 0..2 { ... })
+//
+// A chain of pastes can use several variables, so `Reg~N~_~M` expands to
+// Reg0_0, Reg0_1, Reg1_0 and so on, and `Reg~N~M` to Reg00, Reg01, Reg10.
+//
+// seq! invocations can also be nested. The body of the inner invocation can
+// refer to the variables of the outer one, which are replaced first, while its
+// own variables are left in place for it to replace, even in the middle of a
+// pasted identifier.
+
+use seq::seq;
+
+pub trait Convert<T> {
+    fn bits() -> (u32, u32);
+}
+
+seq!(N in 0..4, M in 0..2 {
+    pub struct Reg~N~_~M;
+
+    impl Reg~N~_~M {
+        pub const INDEX: usize = N * 2 + M;
+    }
+});
+
+seq!(N in 1..3, M in 1..3 {
+    fn product~N~M() -> u32 {
+        N * M
+    }
+});
+
+seq!(N in 0..2 {
+    pub struct Width~N;
+
+    seq!(M in 0..2 {
+        impl Convert<Width~M> for Width~N {
+            fn bits() -> (u32, u32) {
+                (N, M)
+            }
+        }
+
+        const PAIR~N~_~M: usize = N * 10 + M;
+    });
+});
+
+seq!(N in 0..2, M in 0..3 {
+    const ALL: [(usize, usize); 6] = [#((N, M),)*];
+});
+
+fn main() {
+    assert_eq!(Reg0_0::INDEX, 0);
+    assert_eq!(Reg1_1::INDEX, 3);
+    assert_eq!(Reg3_1::INDEX, 7);
+    assert_eq!(product12() + product21() + product22(), 8);
+
+    assert_eq!(<Width1 as Convert<Width0>>::bits(), (1, 0));
+    assert_eq!(PAIR0_1 + PAIR1_0, 11);
+
+    assert_eq!(ALL, [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+}
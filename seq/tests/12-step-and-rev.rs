@@ -0,0 +1,42 @@
+// Ranges do not have to be contiguous and ascending. Like in a for loop, a
+// range can be followed by .step_by(n) to take every n-th value, and by
+// .rev() to go from the end back to the start:
+//
+//     seq!(N in (0..64).step_by(8) { ... })
+//     seq!(N in (0..8).rev() { ... })
+//
+// The bounds of a range, and the step, can also be written as simple constant
+// arithmetic on integer literals, such as 2*8 or (1 << 4), which the macro
+// evaluates at expansion time.
+
+use seq::seq;
+
+seq!(N in (0..64).step_by(8) {
+    const OFFSETS: [u32; 8] = [#(N,)*];
+});
+
+seq!(N in (0..4).rev() {
+    const COUNTDOWN: [u32; 4] = [#(N,)*];
+});
+
+seq!(N in (1..=10).step_by(3).rev() {
+    const MIXED: [u32; 4] = [#(N,)*];
+});
+
+seq!(N in 0..2*8 {
+    const SIXTEEN: usize = 0 #(+ (N - N + 1))*;
+});
+
+seq!(N in 0..(1 << 4) - 1 {
+    fn bit~N() -> u16 {
+        1 << N
+    }
+});
+
+fn main() {
+    assert_eq!(OFFSETS, [0, 8, 16, 24, 32, 40, 48, 56]);
+    assert_eq!(COUNTDOWN, [3, 2, 1, 0]);
+    assert_eq!(MIXED, [10, 7, 4, 1]);
+    assert_eq!(SIXTEEN, 16);
+    assert_eq!(bit0() | bit14(), 0x4001);
+}
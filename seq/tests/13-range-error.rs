@@ -0,0 +1,14 @@
+// Range bounds are evaluated while the macro expands, long before the names in
+// the program are resolved, so they can only be made of integer literals and
+// arithmetic. Anything else should be reported against the offending part of
+// the header.
+
+use seq::seq;
+
+const LEN: usize = 4;
+
+seq!(N in 0..LEN * 2 {
+    fn f~N() {}
+});
+
+fn main() {}
@@ -0,0 +1,69 @@
+// Substituted values are written in the same form as the literals of the
+// range. With
+//
+//     seq!(N in 0x00u8..0x10u8 { ... })
+//
+// N becomes 0x00u8, 0x01u8, ... 0x0fu8, keeping the type suffix as well as the
+// hexadecimal notation and its number of digits. Unsuffixed decimal ranges
+// keep producing unsuffixed decimal literals.
+//
+// Pasted identifiers can pad the value with zeros to a given width, so that
+// `Reg~N:02` yields Reg00 through Reg15 and the names sort lexically.
+//
+// Ranges may also include negative numbers, which are substituted as negative
+// literals in parentheses, so that `N.pow(2)` squares the whole value.
+
+use seq::seq;
+
+fn type_name_of<T>(_: T) -> &'static str {
+    std::any::type_name::<T>()
+}
+
+seq!(N in 0x00u8..0x10u8 {
+    const BYTES: [u8; 16] = [#(N,)*];
+    const SUFFIXED: &str = stringify!(#(N)*);
+});
+
+seq!(N in 0xA0..=0xA2 {
+    const UPPER: &str = stringify!(#(N)*);
+});
+
+seq!(N in 0..16 {
+    #[derive(Debug)]
+    enum Reg {
+        #(
+            Reg~N:02,
+        )*
+    }
+});
+
+seq!(N in -4..4 {
+    const SIGNED: [i32; 8] = [#(N,)*];
+});
+
+seq!(N in (-3..=-1).rev() {
+    const NEGATIVE: [i64; 3] = [#(N * 2,)*];
+});
+
+seq!(N in -3i32..0 {
+    const SQUARES: [i32; 3] = [#(N.pow(2),)*];
+});
+
+fn main() {
+    assert_eq!(BYTES[15], 15);
+    let suffixed: Vec<&str> = SUFFIXED.split_whitespace().collect();
+    assert_eq!(suffixed[..3], ["0x00u8", "0x01u8", "0x02u8"]);
+    assert_eq!(suffixed[10..], ["0x0au8", "0x0bu8", "0x0cu8", "0x0du8", "0x0eu8", "0x0fu8"]);
+    seq!(N in 0u16..1u16 {
+        assert_eq!(type_name_of(N), "u16");
+    });
+    assert_eq!(UPPER, "0xA0 0xA1 0xA2");
+
+    assert_eq!(format!("{:?}", Reg::Reg00), "Reg00");
+    assert_eq!(format!("{:?}", Reg::Reg09), "Reg09");
+    assert_eq!(format!("{:?}", Reg::Reg15), "Reg15");
+
+    assert_eq!(SIGNED, [-4, -3, -2, -1, 0, 1, 2, 3]);
+    assert_eq!(NEGATIVE, [-2, -4, -6]);
+    assert_eq!(SQUARES, [9, 4, 1]);
+}
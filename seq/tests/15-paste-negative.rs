@@ -0,0 +1,10 @@
+// Negative values have no place in an identifier, so pasting one should be
+// reported against the variable.
+
+use seq::seq;
+
+seq!(N in -2..2 {
+    fn f~N() {}
+});
+
+fn main() {}
@@ -0,0 +1,63 @@
+// Besides integer ranges, a loop variable can iterate over an explicit list of
+// token trees written in square brackets:
+//
+//     seq!(T in [u8, u16, u32, u64] { impl Foo for T {} })
+//
+// Several variables can be bound at once by destructuring a list of tuples,
+// and a leading variable followed by a comma counts the repetitions, which is
+// handy for indices and pasted names:
+//
+//     seq!(i, (T, BITS) in [(u8, 8), (u16, 16)] { ... })
+//
+// List values can be pasted into identifiers as long as they are a single
+// identifier or literal, and #(...)* sections work just as with ranges.
+
+use seq::seq;
+
+pub trait Bits {
+    const BITS: u32;
+}
+
+seq!((T, N) in [(u8, 8), (u16, 16), (u32, 32), (u64, 64)] {
+    impl Bits for T {
+        const BITS: u32 = N;
+    }
+});
+
+pub trait Name {
+    fn name() -> &'static str;
+}
+
+seq!(T in [u8, i32, Vec<u8>, (u8, char)] {
+    impl Name for T {
+        fn name() -> &'static str {
+            stringify!(T)
+        }
+    }
+});
+
+seq!(i, T in [u8, u16, u32] {
+    fn index_~T() -> usize {
+        i
+    }
+});
+
+seq!(i, T in [bool, char] {
+    const NAMES: [(usize, &str); 2] = [#((i, stringify!(T)),)*];
+});
+
+seq!(N in 0..2, C in ['a', 'b'] {
+    const PAIRS: [(u8, char); 4] = [#((N, C),)*];
+});
+
+fn main() {
+    assert_eq!(<u8 as Bits>::BITS, 8);
+    assert_eq!(<u64 as Bits>::BITS, 64);
+
+    assert_eq!(<Vec<u8>>::name().replace(' ', ""), "Vec<u8>");
+    assert_eq!(<(u8, char)>::name().replace(' ', ""), "(u8,char)");
+
+    assert_eq!(index_u8() + index_u16() + index_u32(), 3);
+    assert_eq!(NAMES, [(0, "bool"), (1, "char")]);
+    assert_eq!(PAIRS, [(0, 'a'), (0, 'b'), (1, 'a'), (1, 'b')]);
+}
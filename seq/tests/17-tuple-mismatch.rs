@@ -0,0 +1,10 @@
+// Every element of a destructured list needs exactly one value per variable,
+// so a tuple of the wrong size should be reported against that element.
+
+use seq::seq;
+
+seq!((T, BITS) in [(u8, 8), (u16, 16, 2)] {
+    const _: u32 = BITS;
+});
+
+fn main() {}
@@ -0,0 +1,15 @@
+// Padding like `:02` only has a meaning after a pasted loop variable taking
+// integer values, so writing it anywhere else should be reported rather than
+// silently dropped.
+
+use seq::seq;
+
+seq!(N in 0..2 {
+    fn f~N~suffix:02() {}
+});
+
+seq!(T in [u8, u16] {
+    fn g~T:02() {}
+});
+
+fn main() {}
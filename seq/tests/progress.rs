@@ -1,13 +1,22 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse-header.rs");
-    //t.pass("tests/02-parse-body.rs");
-    //t.compile_fail("tests/03-expand-four-errors.rs");
-    //t.pass("tests/04-paste-ident.rs");
-    //t.pass("tests/05-repeat-section.rs");
-    //t.pass("tests/06-init-array.rs");
-    //t.pass("tests/07-inclusive-range.rs");
-    //t.compile_fail("tests/08-ident-span.rs");
-    //t.pass("tests/09-interaction-with-macrorules.rs");
+    t.pass("tests/01-parse-header.rs");
+    t.pass("tests/02-parse-body.rs");
+    t.compile_fail("tests/03-expand-four-errors.rs");
+    t.pass("tests/04-paste-ident.rs");
+    t.pass("tests/05-repeat-section.rs");
+    t.pass("tests/06-init-array.rs");
+    t.pass("tests/07-inclusive-range.rs");
+    t.compile_fail("tests/08-ident-span.rs");
+    t.pass("tests/09-interaction-with-macrorules.rs");
+    t.pass("tests/10-paste-suffix.rs");
+    t.pass("tests/11-multiple-vars.rs");
+    t.pass("tests/12-step-and-rev.rs");
+    t.compile_fail("tests/13-range-error.rs");
+    t.pass("tests/14-literal-form.rs");
+    t.compile_fail("tests/15-paste-negative.rs");
+    t.pass("tests/16-token-lists.rs");
+    t.compile_fail("tests/17-tuple-mismatch.rs");
+    t.compile_fail("tests/18-padding-error.rs");
 }